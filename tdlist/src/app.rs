@@ -1,13 +1,61 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
 
 use crate::{
-    configuration::{get_db_file, Settings},
+    configuration::{get_archive_file, get_db_file, get_undo_file, Settings},
     task::Task,
     utils,
 };
 
 pub type Id = usize;
 
+/// The tasks as they were before the last `apply_changes`, and when it ran
+#[derive(Serialize, Deserialize)]
+struct UndoSnapshot {
+    applied_at: DateTime<Utc>,
+    originals: HashMap<Id, Task>,
+}
+
+impl UndoSnapshot {
+    /// Swaps changed copies into `tasks`, keeping the originals
+    fn apply(tasks: &mut HashMap<Id, Task>, changed: Vec<Task>, now: DateTime<Utc>) -> Self {
+        let mut originals = HashMap::new();
+        for mut task in changed {
+            let Some(id) = task.id else { continue };
+            task.modified_at = Some(now);
+            if let Some(original) = tasks.insert(id, task) {
+                originals.insert(id, original);
+            }
+        }
+        UndoSnapshot {
+            applied_at: now,
+            originals,
+        }
+    }
+
+    /// Puts the originals back into `tasks`, returning how many were
+    /// restored and how many were skipped for having changed since
+    fn restore(self, tasks: &mut HashMap<Id, Task>) -> (usize, usize) {
+        let (mut restored, mut skipped) = (0, 0);
+        for (id, mut original) in self.originals {
+            let untouched = tasks
+                .get(&id)
+                .is_some_and(|t| t.modified_at == Some(self.applied_at));
+            if untouched {
+                original.migrate();
+                tasks.insert(id, original);
+                restored += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+        (restored, skipped)
+    }
+}
+
 pub struct App {
     pub tasks: HashMap<Id, Task>,
     pub settings: Settings,
@@ -26,6 +74,9 @@ impl App {
     }
 
     pub fn add_task(&mut self, mut t: Task) -> Id {
+        let now = Utc::now();
+        t.created_at.get_or_insert(now);
+        t.modified_at = Some(now);
         let new_id = match t.id {
             Some(id) => {
                 self.tasks.insert(id, t);
@@ -47,12 +98,184 @@ impl App {
         self.tasks.get(&id)
     }
 
-    fn save_state(&mut self) {
-        utils::save_tasks(get_db_file(), self);
+    /// Borrows a task in order to change it, which counts as modifying it
+    pub fn get_task_mut(&mut self, id: Id) -> Result<&mut Task> {
+        let task = self
+            .tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("Task {} not found", id))?;
+        task.modified_at = Some(Utc::now());
+        Ok(task)
+    }
+
+    /// Completes the task, or marks it incomplete again if it already was
+    pub fn toggle_complete(&mut self, id: Id) -> Result<()> {
+        let task = self.get_task_mut(id)?;
+        if task.complete {
+            task.uncomplete();
+        } else {
+            task.complete(Utc::now());
+        }
+        self.save_state();
+        Ok(())
+    }
+
+    /// Re-reads tasks from the database, picking up changes made by other
+    /// processes
+    pub fn reload(&mut self) {
+        self.tasks = utils::load_tasks(get_db_file());
+        self.current_id = self.tasks.keys().copied().max().unwrap_or(0);
+    }
+
+    pub fn save_state(&mut self) {
+        utils::save_tasks(get_db_file(), &self.tasks);
+    }
+
+    /// Replaces tasks with changed copies in one save, remembering the
+    /// originals so `undo` can put them back
+    pub fn apply_changes(&mut self, changed: Vec<Task>) {
+        let snapshot = UndoSnapshot::apply(&mut self.tasks, changed, Utc::now());
+        let json = serde_json::to_string(&snapshot).expect("Failed to serialize undo to json");
+        fs::write(get_undo_file(), json).expect("Unable to write undo file");
+        self.save_state();
+    }
+
+    /// Restores the tasks changed by the last `apply_changes`, returning how
+    /// many were restored and how many were skipped. Tasks changed or
+    /// archived since are skipped so later work isn't lost.
+    pub fn undo(&mut self) -> Result<(usize, usize)> {
+        let file = get_undo_file();
+        if !file.exists() {
+            return Err(anyhow!("Nothing to undo"));
+        }
+        let snapshot: UndoSnapshot = serde_json::from_str(&fs::read_to_string(&file)?)
+            .context("Unable to parse undo file")?;
+        let (restored, skipped) = snapshot.restore(&mut self.tasks);
+        self.save_state();
+        fs::remove_file(file)?;
+        Ok((restored, skipped))
+    }
+
+    /// Archived tasks live in their own file so they aren't read on every
+    /// run. Only commands that need them load it.
+    pub fn load_archive(&self) -> HashMap<Id, Task> {
+        utils::load_tasks(get_archive_file())
     }
 
-    fn get_next_id(&mut self) -> usize {
-        self.current_id += 1;
+    /// Completed tasks that haven't changed since before `cutoff`, or all
+    /// completed tasks without one
+    pub fn archivable(&self, cutoff: Option<DateTime<Utc>>) -> Vec<Id> {
+        self.tasks
+            .values()
+            .filter(|t| t.complete)
+            .filter(|t| match (cutoff, t.modified_at.or(t.completed_at)) {
+                (Some(cutoff), Some(changed)) => changed <= cutoff,
+                _ => true,
+            })
+            .filter_map(|t| t.id)
+            .collect()
+    }
+
+    /// Moves tasks into the archive. The archive is written first so a
+    /// failure can only leave a task in both files, never in neither.
+    pub fn archive(&mut self, ids: &[Id]) -> usize {
+        if ids.is_empty() {
+            return 0;
+        }
+        let mut archive = self.load_archive();
+        for id in ids {
+            if let Some(task) = self.tasks.remove(id) {
+                archive.insert(*id, task);
+            }
+        }
+        utils::save_tasks(get_archive_file(), &archive);
+        self.save_state();
+        ids.len()
+    }
+
+    /// Moves a task out of the archive and back into the task list
+    pub fn unarchive(&mut self, id: Id) -> Result<()> {
+        let mut archive = self.load_archive();
+        let mut task = archive
+            .remove(&id)
+            .ok_or_else(|| anyhow!("Task {} is not archived", id))?;
+        task.modified_at = Some(Utc::now());
+        self.tasks.insert(id, task);
+        self.save_state();
+        utils::save_tasks(get_archive_file(), &archive);
+        Ok(())
+    }
+
+    /// Archives old completed tasks if the `auto_archive` setting asks for it
+    pub fn auto_archive(&mut self) -> Result<usize> {
+        let Some(age) = &self.settings.auto_archive else {
+            return Ok(0);
+        };
+        let age = utils::parse_duration(age).context("Invalid auto_archive setting")?;
+        let ids = self.archivable(Some(utils::time_ago(age)?));
+        Ok(self.archive(&ids))
+    }
+
+    /// Ids keep counting up past archived tasks so unarchiving one can never
+    /// clash with a newer task. Each call hands out a new id.
+    pub fn get_next_id(&mut self) -> usize {
+        let archived = self.load_archive().keys().copied().max().unwrap_or(0);
+        self.current_id = self.current_id.max(archived) + 1;
         self.current_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: Id, group: &str) -> Task {
+        let mut task = Task::default();
+        task.set_id(Some(id));
+        task.set_name(format!("task {}", id));
+        task.set_group(group.to_string());
+        task
+    }
+
+    fn tasks() -> HashMap<Id, Task> {
+        (1..=3).map(|id| (id, task(id, "home"))).collect()
+    }
+
+    fn group(tasks: &HashMap<Id, Task>, id: Id) -> Option<&str> {
+        tasks[&id].group.as_deref()
+    }
+
+    #[test]
+    fn undo_restores_the_originals() {
+        let mut tasks = tasks();
+        let changed = vec![task(1, "work"), task(2, "work")];
+        let snapshot = UndoSnapshot::apply(&mut tasks, changed, Utc::now());
+        assert_eq!(group(&tasks, 1), Some("work"));
+
+        // The undo file is read back from json
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: UndoSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.restore(&mut tasks), (2, 0));
+        assert_eq!(group(&tasks, 1), Some("home"));
+        assert_eq!(group(&tasks, 2), Some("home"));
+    }
+
+    #[test]
+    fn undo_skips_tasks_changed_or_archived_since() {
+        let mut tasks = tasks();
+        let applied_at = Utc::now();
+        let changed = vec![task(1, "work"), task(2, "work"), task(3, "work")];
+        let snapshot = UndoSnapshot::apply(&mut tasks, changed, applied_at);
+
+        let later = tasks.get_mut(&1).unwrap();
+        later.complete(applied_at);
+        later.modified_at = Some(applied_at + chrono::Duration::minutes(1));
+        tasks.remove(&2);
+
+        assert_eq!(snapshot.restore(&mut tasks), (1, 2));
+        assert!(tasks[&1].complete);
+        assert_eq!(group(&tasks, 1), Some("work"));
+        assert!(!tasks.contains_key(&2));
+        assert_eq!(group(&tasks, 3), Some("home"));
+    }
+}
@@ -1,30 +1,69 @@
 use crate::app::App;
+use crate::configuration::{get_attachments_dir, Settings};
+use crate::link::{Link, LinkTarget};
+use crate::utils;
 
 use crate::cli::cli_utils;
 use crate::cli::formats::Format;
+use crate::quick_add::QuickAdd;
+use crate::task::Task;
 use crate::task_form::TaskForm;
 use anyhow::Result;
+use chrono_tz::Tz;
 use clap::Parser;
+use std::fs;
+use std::path::Path;
 
 #[derive(Parser)]
 pub struct Args {
-    /// The name of the new task
+    /// The name of the new task. Unless --literal is given, dates, times,
+    /// repeats, #group, !priority, +tags and @url are picked out of it.
     name: String,
+    /// Use the name exactly as typed
+    #[arg(long)]
+    literal: bool,
     /// The date the task is due
     #[arg(long)]
     date: Option<String>,
-    /// How often the task repeats
+    /// The date to start showing the task from
+    #[arg(long)]
+    scheduled: Option<String>,
+    /// The timezone the date is given in, e.g. Europe/Berlin (defaults to local)
+    #[arg(long)]
+    timezone: Option<String>,
+    /// How often the task repeats, e.g. weekly or "every 3 days after completion"
     #[arg(long)]
     repeats: Option<String>,
-    /// The group the task belongs to
+    /// The group the task belongs to, defaulting to the current context
     #[arg(long)]
     group: Option<String>,
+    /// How important the task is: low, medium or high
+    #[arg(long)]
+    priority: Option<String>,
+    /// A tag for the task, can be given several times
+    #[arg(long)]
+    tag: Vec<String>,
     /// A description or url for your task
     #[arg(long)]
     description: Option<String>,
     /// A url for your task
     #[arg(long)]
     url: Option<String>,
+    /// A labelled link as label=url, can be given several times
+    #[arg(long)]
+    link: Vec<String>,
+    /// A local file to attach, can be given several times
+    #[arg(long)]
+    attach: Vec<String>,
+    /// Copy attached files into the data directory instead of referencing them
+    #[arg(long, requires = "attach")]
+    copy: bool,
+    /// How long before the task to be reminded, e.g. 30m,1d
+    #[arg(long)]
+    remind: Option<String>,
+    /// How long the task is expected to take, e.g. 2h or 1h30m
+    #[arg(long)]
+    estimate: Option<String>,
     /// The format to display the new task with
     #[arg(long)]
     format: Option<Format>,
@@ -33,25 +72,131 @@ pub struct Args {
 pub fn run(mut app: App, args: Args) -> Result<()> {
     let Args {
         name,
+        literal,
         format,
         date,
+        scheduled,
+        timezone,
         repeats,
         group,
+        priority,
+        tag,
         description,
         url,
+        link,
+        attach,
+        copy,
+        remind,
+        estimate,
     } = args;
+    let mut quick = if literal {
+        QuickAdd {
+            name,
+            ..Default::default()
+        }
+    } else {
+        match timezone.as_deref().map(str::parse::<Tz>) {
+            Some(Ok(tz)) => QuickAdd::parse_in(&name, &tz, &app.settings)?,
+            // The form reports a bad timezone
+            Some(Err(_)) | None => QuickAdd::parse(&name, &app.settings)?,
+        }
+    };
+    // Flags win over anything picked out of the name
+    if date.is_some() {
+        quick.date = None;
+    }
+    if repeats.is_some() {
+        quick.repeats = None;
+    }
+    if group.is_some() {
+        quick.group = None;
+    }
+    if priority.is_some() {
+        quick.priority = None;
+    }
+    if url.is_some() {
+        quick.url = None;
+    }
+    let mut tags = quick.tags.clone();
+    tags.extend(tag);
     let mut task_form = TaskForm {
         id: None,
-        name,
+        name: quick.name.clone(),
         date: date.unwrap_or("".to_string()),
-        repeats: repeats.unwrap_or("".to_string()),
-        group: group.unwrap_or("".to_string()),
+        timezone: timezone.unwrap_or("".to_string()),
+        scheduled: scheduled.unwrap_or("".to_string()),
+        repeats: repeats
+            .or(quick.repeats.as_ref().map(|r| r.to_string()))
+            .unwrap_or("".to_string()),
+        group: group
+            .or(quick.group.clone())
+            .or_else(|| app.settings.current_group.clone())
+            .unwrap_or("".to_string()),
+        priority: priority
+            .or(quick.priority.map(|p| p.to_string()))
+            .unwrap_or("".to_string()),
+        tags,
         description: description.unwrap_or("".to_string()),
-        url: url.unwrap_or("".to_string()),
+        url: url.or(quick.url.clone()).unwrap_or("".to_string()),
+        reminders: remind.unwrap_or("".to_string()),
+        estimate: estimate.unwrap_or("".to_string()),
+        links: link,
+        attachments: attach,
     };
-    let task = task_form.submit(&app.settings)?;
+    let mut task = task_form.submit(&app.settings)?;
+    if let Some(date) = quick.date {
+        task.set_date(date);
+    }
+    if copy {
+        // Copy before saving so a failed copy doesn't leave a half-made task
+        let id = app.get_next_id();
+        task.set_id(Some(id));
+        let dir = get_attachments_dir().join(id.to_string());
+        if let Err(e) = copy_attachments(&mut task, &dir) {
+            fs::remove_dir_all(&dir).ok();
+            return Err(e);
+        }
+    }
     let id = app.add_task(task);
     let task = app.get_task(id).unwrap();
+    if quick.extracted_anything() && matches!(format, None | Some(Format::PlainText)) {
+        print_extracted(&quick, &app.settings);
+    }
     cli_utils::print_task(task, format, &app.settings);
     Ok(())
 }
+
+/// Swaps the task's file links for copies in `dir`
+fn copy_attachments(task: &mut Task, dir: &Path) -> Result<()> {
+    for link in task.links.iter_mut() {
+        if let LinkTarget::File(path) = &link.target {
+            *link = Link::copy_file(path, link.label.clone(), dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Shows what was picked out of the name, so surprises are easy to spot
+fn print_extracted(quick: &QuickAdd, settings: &Settings) {
+    println!("Picked out of the name (use --literal to keep it as typed):");
+    let field = |name: &str, value: String| println!("  {:9} {}", name, value);
+    if let Some(date) = &quick.date {
+        field("Date", utils::date_to_display_str(date, settings));
+    }
+    if let Some(repeats) = &quick.repeats {
+        field("Repeats", repeats.to_string());
+    }
+    if let Some(group) = &quick.group {
+        field("Group", group.clone());
+    }
+    if let Some(priority) = quick.priority {
+        field("Priority", priority.to_string());
+    }
+    if !quick.tags.is_empty() {
+        field("Tags", quick.tags.join(", "));
+    }
+    if let Some(url) = &quick.url {
+        field("Url", url.clone());
+    }
+    println!();
+}
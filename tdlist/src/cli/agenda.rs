@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use crate::app::App;
+use crate::due_date::DueDate;
+use crate::occurrence;
+use crate::task::Task;
+use crate::utils;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use clap::Parser;
+use serde::Serialize;
+
+use super::formats::Format;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The first day to show, defaults to today
+    #[arg(long)]
+    from: Option<String>,
+    /// The last day to show, defaults to a week after the first
+    #[arg(long)]
+    to: Option<String>,
+    /// Only show tasks in this group
+    #[arg(long)]
+    group: Option<String>,
+    /// The format to print the agenda with
+    #[arg(short, long)]
+    format: Option<Format>,
+}
+
+#[derive(Serialize)]
+struct AgendaEntry<'a> {
+    due: DueDate,
+    /// Whether this is a future occurrence of a repeating task rather than
+    /// its current due date
+    is_virtual: bool,
+    /// Whether the task was due before the first day shown and is listed
+    /// on it because it's still to do
+    overdue: bool,
+    task: &'a Task,
+}
+
+#[derive(Serialize)]
+struct AgendaDay<'a> {
+    date: NaiveDate,
+    entries: Vec<AgendaEntry<'a>>,
+}
+
+fn parse_day(s: Option<String>, app: &App) -> Result<Option<NaiveDate>> {
+    s.map(|s| utils::parse_relative_date(&s, &app.settings).map(|d| d.local_date()))
+        .transpose()
+        .context("Invalid agenda date")
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let from = parse_day(args.from, &app)?.unwrap_or(Local::now().date_naive());
+    let to = parse_day(args.to, &app)?.unwrap_or(from + Duration::days(7));
+
+    let mut days: BTreeMap<NaiveDate, Vec<AgendaEntry>> = BTreeMap::new();
+    let tasks = app
+        .tasks
+        .values()
+        .filter(|t| !t.complete)
+        .filter(|t| args.group.as_ref().is_none_or(|g| t.in_group(g)));
+    for task in tasks {
+        if task.date.local_date() < from {
+            days.entry(from).or_default().push(AgendaEntry {
+                due: task.date,
+                is_virtual: false,
+                overdue: true,
+                task,
+            });
+        }
+        for due in occurrence::occurrences_between(task, from, to) {
+            days.entry(due.local_date()).or_default().push(AgendaEntry {
+                due,
+                is_virtual: due != task.date,
+                overdue: false,
+                task,
+            });
+        }
+    }
+    let days: Vec<AgendaDay> = days
+        .into_iter()
+        .map(|(date, mut entries)| {
+            entries.sort_by(|a, b| {
+                a.due
+                    .cmp(&b.due)
+                    .then_with(|| a.task.name.cmp(&b.task.name))
+            });
+            AgendaDay { date, entries }
+        })
+        .collect();
+
+    match args.format {
+        Some(Format::Json) => println!(
+            "{}",
+            serde_json::to_string(&days).expect("Failed to serialize agenda to json")
+        ),
+        Some(Format::JsonPretty) => println!(
+            "{}",
+            serde_json::to_string_pretty(&days).expect("Failed to serialize agenda to json")
+        ),
+        _ => print_agenda(&days, &app),
+    }
+    Ok(())
+}
+
+fn print_agenda(days: &[AgendaDay], app: &App) {
+    let settings = &app.settings;
+    let name_id = |task: &Task| format!("{} ({})", task.name, task.id.unwrap());
+    let longest_name = days
+        .iter()
+        .flat_map(|d| d.entries.iter())
+        .map(|e| name_id(e.task).len())
+        .max()
+        .unwrap_or(0);
+    for day in days {
+        println!(
+            "{} {}",
+            day.date.format(&settings.date_formats.display_date_format),
+            day.date.weekday()
+        );
+        for entry in &day.entries {
+            let icon = if entry.is_virtual {
+                format!(" {}", settings.icons.repeats)
+            } else {
+                settings.icons.get_complete_icon(entry.task.complete)
+            };
+            let time = match entry.due {
+                DueDate::DateTime(dt) => utils::to_local(&dt).format("%H:%M").to_string(),
+                DueDate::Date(_) => String::new(),
+            };
+            let group = entry.task.group.as_deref().unwrap_or_default();
+            let overdue = if entry.overdue {
+                format!(
+                    "  overdue since {}",
+                    utils::date_to_display_str(&entry.due, settings)
+                )
+            } else {
+                String::new()
+            };
+            println!(
+                "  {} {:width$}  {:5}  {}{}",
+                icon,
+                name_id(entry.task),
+                time,
+                group,
+                overdue,
+                width = longest_name
+            );
+        }
+    }
+}
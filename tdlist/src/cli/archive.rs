@@ -0,0 +1,35 @@
+use crate::app::{App, Id};
+use crate::utils;
+
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Only archive tasks that haven't changed for this long, e.g. 30d
+    #[arg(long)]
+    older_than: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct UnarchiveArgs {
+    /// The id of the archived task
+    id: Id,
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let cutoff = match args.older_than {
+        Some(age) => Some(utils::time_ago(utils::parse_duration(&age)?)?),
+        None => None,
+    };
+    let ids = app.archivable(cutoff);
+    let count = app.archive(&ids);
+    println!("Archived {} tasks", count);
+    Ok(())
+}
+
+pub fn run_unarchive(mut app: App, args: UnarchiveArgs) -> Result<()> {
+    app.unarchive(args.id)?;
+    println!("Restored task {}", args.id);
+    Ok(())
+}
@@ -0,0 +1,288 @@
+use crate::app::{App, Id};
+use crate::configuration::Settings;
+use crate::due_date::DueDate;
+use crate::repeat::Repeat;
+use crate::task::Task;
+use crate::utils;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+
+use super::ls::{self, Filters};
+
+#[derive(Parser)]
+pub struct Args {
+    /// The tasks to change, picked with the same options as `ls`, e.g.
+    /// "--group work --date-filter past"
+    #[arg(long, default_value = "", allow_hyphen_values = true)]
+    filter: String,
+    /// Show what would change without changing anything
+    #[arg(long, global = true)]
+    dry_run: bool,
+    #[command(subcommand)]
+    action: Action,
+}
+
+#[derive(Subcommand)]
+enum Action {
+    /// Sets fields on every task, e.g. group=work or date=tomorrow. Fields
+    /// are group, date, scheduled, repeats and description.
+    Set {
+        /// The fields to set as field=value
+        #[arg(required = true)]
+        fields: Vec<String>,
+    },
+    /// Completes every task
+    Complete,
+    /// Moves every task by a duration, e.g. +1w or -2d
+    ShiftDate {
+        /// How far to move the tasks
+        #[arg(allow_hyphen_values = true)]
+        by: String,
+    },
+}
+
+/// Splits the filter into words the way a shell would, keeping quoted
+/// strings together
+fn split_words(s: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in s.chars() {
+        match (c, quote) {
+            (c, Some(q)) if c == q => quote = None,
+            (c, Some(_)) => word.get_or_insert_with(String::new).push(c),
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, None) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(anyhow!("Unterminated quote in filter: {}", s));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+fn parse_filters(filter: &str) -> Result<Filters> {
+    let words = split_words(filter)?;
+    Filters::try_parse_from(std::iter::once("--filter".to_string()).chain(words)).map_err(|e| {
+        let message = e.render().to_string();
+        let message = message.lines().next().unwrap_or_default();
+        anyhow!("Invalid filter: {}", message.trim_start_matches("error: "))
+    })
+}
+
+fn set_field(task: &mut Task, field: &str, settings: &Settings) -> Result<()> {
+    let (name, value) = field
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Expected field=value, got {}", field))?;
+    let value = value.trim();
+    match name.trim() {
+        "group" => task.set_group(value.to_string()),
+        "date" => task.move_to(utils::parse_relative_date(value, settings)?),
+        "scheduled" if value.is_empty() => task.scheduled = None,
+        "scheduled" => task.set_scheduled(utils::parse_relative_date(value, settings)?),
+        "repeats" if value.is_empty() => task.set_repeats(Repeat::Never),
+        "repeats" => task.set_repeats(Repeat::parse_from_str(value)?),
+        "description" if value.is_empty() => task.description = None,
+        "description" => task.set_description(value.to_string()),
+        other => {
+            return Err(anyhow!(
+                "Unknown field {}, expected group, date, scheduled, repeats or description",
+                other
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn apply(task: &mut Task, action: &Action, settings: &Settings) -> Result<()> {
+    match action {
+        Action::Set { fields } => {
+            for field in fields {
+                set_field(task, field, settings)?;
+            }
+        }
+        Action::Complete => {
+            if !task.complete {
+                task.complete(Utc::now());
+            }
+        }
+        Action::ShiftDate { by } => task.snooze(utils::parse_signed_duration(by)?)?,
+    }
+    Ok(())
+}
+
+/// The fields that differ between the two versions of a task, as
+/// "field: old -> new"
+fn describe_changes(before: &Task, after: &Task, settings: &Settings) -> Vec<String> {
+    let date = |d: Option<&DueDate>| {
+        d.map(|d| utils::date_to_display_str(d, settings))
+            .unwrap_or_default()
+    };
+    let fields = [
+        ("date", date(Some(&before.date)), date(Some(&after.date))),
+        (
+            "scheduled",
+            date(before.scheduled.as_ref()),
+            date(after.scheduled.as_ref()),
+        ),
+        (
+            "group",
+            before.group.clone().unwrap_or_default(),
+            after.group.clone().unwrap_or_default(),
+        ),
+        (
+            "repeats",
+            before.repeats.to_string(),
+            after.repeats.to_string(),
+        ),
+        (
+            "description",
+            before.description.clone().unwrap_or_default(),
+            after.description.clone().unwrap_or_default(),
+        ),
+        (
+            "complete",
+            before.complete.to_string(),
+            after.complete.to_string(),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(name, old, new)| format!("{}: {} -> {}", name, old, new))
+        .collect()
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let Args {
+        filter,
+        dry_run,
+        action,
+    } = args;
+    let filters = parse_filters(&filter)?;
+    let tasks = filters.apply(app.tasks.clone(), &app.settings)?;
+    let mut tasks: Vec<&Task> = tasks.values().collect();
+    ls::sort_tasks(&mut tasks, ls::SortBy::Date);
+
+    // Work on copies so nothing is saved unless every task can be changed
+    let mut changed: Vec<(Id, Task, Vec<String>)> = Vec::new();
+    for task in tasks {
+        let mut after = task.clone();
+        let id = task.id.unwrap();
+        apply(&mut after, &action, &app.settings)
+            .with_context(|| format!("Task {} ({})", task.name, id))?;
+        let changes = describe_changes(task, &after, &app.settings);
+        if !changes.is_empty() {
+            changed.push((id, after, changes));
+        }
+    }
+
+    if changed.is_empty() {
+        println!("No tasks to change");
+        return Ok(());
+    }
+    let longest_name = changed
+        .iter()
+        .map(|(_, t, _)| t.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Name".len());
+    println!(
+        "{:>4}  {:width$}  Changes",
+        "Id",
+        "Name",
+        width = longest_name
+    );
+    for (id, task, changes) in &changed {
+        println!(
+            "{:>4}  {:width$}  {}",
+            id,
+            task.name,
+            changes.join(", "),
+            width = longest_name
+        );
+    }
+
+    let count = changed.len();
+    if dry_run {
+        println!("Dry run, {} tasks would change", count);
+    } else {
+        app.apply_changes(changed.into_iter().map(|(_, task, _)| task).collect());
+        println!("Changed {} tasks, run `tdlist undo` to revert", count);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::default_settings;
+    use std::collections::HashMap;
+
+    #[test]
+    fn splits_words_like_a_shell() {
+        assert_eq!(
+            split_words(r#"--group "home garden" --date-filter  past"#).unwrap(),
+            ["--group", "home garden", "--date-filter", "past"]
+        );
+        assert_eq!(
+            split_words("--group 'it''s' ''").unwrap(),
+            ["--group", "its", ""]
+        );
+        assert!(split_words("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        let error = split_words(r#"--group "home"#).unwrap_err();
+        assert!(error.to_string().starts_with("Unterminated quote"));
+    }
+
+    #[test]
+    fn filters_like_ls() {
+        let task = |id: Id, group: &str, complete: bool| {
+            let mut task = Task::default();
+            task.set_id(Some(id));
+            task.set_name(format!("task {}", id));
+            task.set_group(group.to_string());
+            task.complete = complete;
+            (id, task)
+        };
+        let tasks: HashMap<Id, Task> = [
+            task(1, "home/garden", false),
+            task(2, "work", false),
+            task(3, "home", true),
+        ]
+        .into();
+        let ids = |filter: &str| {
+            let filters = parse_filters(filter).unwrap();
+            let mut ids: Vec<Id> = filters
+                .apply(tasks.clone(), &default_settings())
+                .unwrap()
+                .into_keys()
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(""), [1, 2]);
+        assert_eq!(ids("--group 'Home'"), [1]);
+        assert_eq!(ids("--group home --show-complete"), [1, 3]);
+    }
+
+    #[test]
+    fn rejects_unknown_filters() {
+        let error = parse_filters("--colour red").err().unwrap().to_string();
+        assert!(
+            error.starts_with("Invalid filter: unexpected argument"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+use crate::app::App;
+use crate::configuration::{Color, Settings};
+use crate::occurrence;
+use crate::utils;
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+use clap::Parser;
+use crossterm::style::Stylize;
+
+/// Task names shown in a single day before the rest are summarised
+const MAX_NAMES_PER_DAY: usize = 3;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Show the week around the date
+    #[arg(long, conflicts_with = "month")]
+    week: bool,
+    /// Show the month around the date (the default)
+    #[arg(long)]
+    month: bool,
+    /// List task names in each day instead of counting them
+    #[arg(long)]
+    names: bool,
+    /// Any day in the period to show: today, tomorrow, a weekday or a full
+    /// date. Defaults to today.
+    date: Option<String>,
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let Args {
+        week,
+        month: _,
+        names,
+        date,
+    } = args;
+    let settings = &app.settings;
+    let today = Local::now().date_naive();
+    let date = match date {
+        Some(date) => utils::parse_relative_date(&date, settings)?.local_date(),
+        None => today,
+    };
+
+    // Weeks start on Monday, and months are padded out to whole weeks
+    let (title, first, last) = if week {
+        let first = week_start(date);
+        let title = format!(
+            "Week of {}",
+            first.format(&settings.date_formats.display_date_format)
+        );
+        (title, first, first + Duration::days(6))
+    } else {
+        let first = date.with_day(1).unwrap();
+        let last = first + Months::new(1) - Duration::days(1);
+        (first.format("%B %Y").to_string(), first, last)
+    };
+    let grid_start = week_start(first);
+    let grid_end = week_start(last) + Duration::days(6);
+
+    let mut days: HashMap<NaiveDate, Vec<String>> = HashMap::new();
+    for task in app.tasks.values().filter(|t| !t.complete) {
+        for due in occurrence::occurrences_between(task, first, last) {
+            days.entry(due.local_date())
+                .or_default()
+                .push(task.name.clone());
+        }
+    }
+    for names in days.values_mut() {
+        names.sort();
+    }
+
+    let show_names = names || week;
+    let width = if show_names { 16 } else { 8 };
+    let colored = std::io::stdout().is_terminal();
+
+    println!("{}", title);
+    let header: Vec<String> = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        .iter()
+        .map(|d| format!("{:^width$}", d))
+        .collect();
+    println!("{}", header.join(" ").trim_end());
+
+    let mut week_start = grid_start;
+    while week_start <= grid_end {
+        let cells: Vec<Vec<String>> = (0..7)
+            .map(|i| week_start + Duration::days(i))
+            .map(|day| {
+                if day < first || day > last {
+                    return vec![];
+                }
+                let tasks = days.get(&day).map(|t| t.as_slice()).unwrap_or_default();
+                day_cell(day, tasks, show_names, width)
+            })
+            .collect();
+
+        let height = cells.iter().map(|c| c.len()).max().unwrap_or(0);
+        for line in 0..height {
+            let row: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    let day = week_start + Duration::days(i as i64);
+                    let text = format!("{:width$}", cell.get(line).map_or("", |s| s));
+                    match day_color(day, today, &days, settings) {
+                        Some(color) if colored => text.with(color).to_string(),
+                        _ => text,
+                    }
+                })
+                .collect();
+            println!("{}", row.join(" ").trim_end());
+        }
+        if show_names {
+            println!();
+        }
+        week_start += Duration::days(7);
+    }
+    Ok(())
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+fn day_cell(day: NaiveDate, tasks: &[String], show_names: bool, width: usize) -> Vec<String> {
+    if !show_names {
+        return match tasks.len() {
+            0 => vec![format!("{:>2}", day.day())],
+            n => vec![format!("{:>2} ({})", day.day(), n)],
+        };
+    }
+
+    let mut lines = vec![format!("{:>2}", day.day())];
+    for name in tasks.iter().take(MAX_NAMES_PER_DAY) {
+        lines.push(truncate(name, width));
+    }
+    if tasks.len() > MAX_NAMES_PER_DAY {
+        lines.push(format!("+{} more", tasks.len() - MAX_NAMES_PER_DAY));
+    }
+    lines
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_string();
+    }
+    let mut s: String = s.chars().take(width - 1).collect();
+    s.push('…');
+    s
+}
+
+/// Days with tasks left over from the past are marked as overdue, and today
+/// is highlighted
+fn day_color(
+    day: NaiveDate,
+    today: NaiveDate,
+    days: &HashMap<NaiveDate, Vec<String>>,
+    settings: &Settings,
+) -> Option<crossterm::style::Color> {
+    let color = if day < today && days.contains_key(&day) {
+        settings.colors.overdue_color
+    } else if day == today {
+        settings.colors.accent_color
+    } else {
+        return None;
+    };
+    Some(Color::from_tui_color(color).to_crossterm_color())
+}
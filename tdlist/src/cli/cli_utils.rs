@@ -1,11 +1,29 @@
 use super::formats::Format;
 use crate::{configuration::Settings, task::Task, utils};
+use itertools::Itertools;
 
 pub fn print_task(task: &Task, format: Option<Format>, settings: &Settings) {
     let tasks = vec![task];
     print_tasks(tasks, format, true, true, settings)
 }
 
+fn scheduled_to_display_str(task: &Task, settings: &Settings) -> String {
+    task.scheduled
+        .as_ref()
+        .map(|d| utils::date_to_display_str(d, settings))
+        .unwrap_or_default()
+}
+
+/// Time logged, out of the estimate if there is one, e.g. 1h30m/2h
+fn time_to_display_str(task: &Task, now: chrono::DateTime<chrono::Utc>) -> String {
+    let logged = utils::duration_to_display_str(&task.time_logged(None, None, now));
+    match task.estimate() {
+        Some(estimate) => format!("{}/{}", logged, utils::duration_to_display_str(&estimate)),
+        None if task.time_entries.is_empty() => String::new(),
+        None => logged,
+    }
+}
+
 pub fn print_tasks(
     tasks: Vec<&Task>,
     format: Option<Format>,
@@ -29,6 +47,12 @@ pub fn print_tasks(
                 .map(|t| utils::date_to_display_str(&t.date, settings).len())
                 .max()
                 .unwrap_or(0);
+            let longest_scheduled = tasks
+                .iter()
+                .map(|t| scheduled_to_display_str(t, settings).len())
+                .max()
+                .unwrap_or(0)
+                .max("Scheduled".len());
             let longest_repeat = tasks
                 .iter()
                 .map(|t| t.repeats.to_string().len())
@@ -40,17 +64,33 @@ pub fn print_tasks(
                 .max()
                 .unwrap_or(0);
 
+            // Time only gets a column once some task is estimated or timed
+            let now = chrono::Utc::now();
+            let show_time = tasks
+                .iter()
+                .any(|t| t.estimate_minutes.is_some() || !t.time_entries.is_empty());
+            let longest_time = tasks
+                .iter()
+                .map(|t| time_to_display_str(t, now).len())
+                .max()
+                .unwrap_or(0)
+                .max("Time".len());
+
             // Print header
             print!("{:width$}  ", "Name", width = longest_name + 10);
             print!("{:width$}  ", "Date", width = longest_date);
+            print!("{:width$}  ", "Scheduled", width = longest_scheduled);
             print!("{:width$}\t", "Repeats", width = longest_repeat);
             print!("{:width$}\t", "Group", width = longest_group);
+            if show_time {
+                print!("{:width$}  ", "Time", width = longest_time);
+            }
 
             if show_descriptions {
                 print!("Description  ")
             }
             if show_urls {
-                print!("Url  ")
+                print!("Links  ")
             }
             println!();
 
@@ -67,20 +107,27 @@ pub fn print_tasks(
                 let date = utils::date_to_display_str(&task.date, settings);
                 print!("{:width$}  ", date, width = longest_date);
 
+                let scheduled = scheduled_to_display_str(task, settings);
+                print!("{:width$}  ", scheduled, width = longest_scheduled);
+
                 let repeats = &task.repeats;
                 print!("{:width$}\t", repeats, width = longest_repeat);
 
                 let group = &task.group.as_deref().unwrap_or_default();
                 print!("{:width$}\t", group, width = longest_group);
 
+                if show_time {
+                    let time = time_to_display_str(task, now);
+                    print!("{:width$}  ", time, width = longest_time);
+                }
+
                 if show_descriptions {
                     let description = task.description.clone();
                     print!("{}  ", description.unwrap_or(String::from("")));
                 }
 
                 if show_urls {
-                    let url = task.url.clone();
-                    print!("{}  ", url.unwrap_or(String::from("")));
+                    print!("{}  ", task.links.iter().join(", "));
                 }
 
                 println!();
@@ -1,12 +1,24 @@
-use crate::app::App;
+use crate::app::{App, Id};
 
 use anyhow::Result;
 use clap::Parser;
 
+use super::cli_utils;
+use super::formats::Format;
+
 #[derive(Parser)]
-pub struct Args {}
+pub struct Args {
+    /// The id of the task to complete
+    id: Id,
+    /// The format to display the task with
+    #[arg(long)]
+    format: Option<Format>,
+}
 
-pub fn run(app: App, args: Args) -> Result<()> {
-    // TODO:
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let Args { id, format } = args;
+    app.toggle_complete(id)?;
+    let task = app.get_task(id).unwrap();
+    cli_utils::print_task(task, format, &app.settings);
     Ok(())
 }
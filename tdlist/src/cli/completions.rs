@@ -0,0 +1,190 @@
+use std::io;
+
+use crate::app::App;
+use crate::group;
+
+use anyhow::Result;
+use clap::{Command, Parser, ValueEnum};
+use clap_complete::Shell;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The shell to generate completions for
+    shell: Shell,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Candidates {
+    Ids,
+    Groups,
+}
+
+#[derive(Parser)]
+pub struct HelperArgs {
+    /// What to list
+    candidates: Candidates,
+}
+
+/// The subcommands, as paths of names, whose positionals are task ids or
+/// group names
+#[derive(Default)]
+struct DynamicArgs {
+    ids: Vec<String>,
+    groups: Vec<String>,
+}
+
+fn find_dynamic_args(cmd: &Command, path: &mut Vec<String>, found: &mut DynamicArgs) {
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        path.push(sub.get_name().to_string());
+        for arg in sub.get_positionals() {
+            match arg.get_id().as_str() {
+                "id" => found.ids.push(path.join(" ")),
+                "group" | "groups" => found.groups.push(path.join(" ")),
+                _ => {}
+            }
+        }
+        find_dynamic_args(sub, path, found);
+        path.pop();
+    }
+}
+
+const BASH: &str = r#"
+_tdlist_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local cmd_path="${COMP_WORDS[*]:1:COMP_CWORD-1}"
+    if [[ "$prev" == "--group" ]]; then
+        COMPREPLY=($(compgen -W "$(tdlist __complete groups)" -- "$cur"))
+        return
+    fi
+    case "$cmd_path" in
+        @IDS@)
+            COMPREPLY=($(compgen -W "$(tdlist __complete ids | cut -f1)" -- "$cur"))
+            return
+            ;;
+        @GROUPS@)
+            COMPREPLY=($(compgen -W "$(tdlist __complete groups)" -- "$cur"))
+            return
+            ;;
+    esac
+    _tdlist "$@"
+}
+complete -F _tdlist_dynamic -o bashdefault -o default tdlist
+"#;
+
+const ZSH: &str = r#"
+_tdlist_dynamic() {
+    local cmd_path="${(j: :)words[2,CURRENT-1]}"
+    local -a candidates
+    if [[ "${words[CURRENT-1]}" == "--group" ]]; then
+        candidates=(${(f)"$(tdlist __complete groups)"})
+        compadd -a candidates
+        return
+    fi
+    case "$cmd_path" in
+        (@IDS@)
+            candidates=(${(f)"$(tdlist __complete ids)"})
+            candidates=(${candidates//:/\\:})
+            candidates=(${candidates//$'\t'/:})
+            _describe 'task' candidates
+            return
+            ;;
+        (@GROUPS@)
+            candidates=(${(f)"$(tdlist __complete groups)"})
+            compadd -a candidates
+            return
+            ;;
+    esac
+    _tdlist "$@"
+}
+compdef _tdlist_dynamic tdlist
+"#;
+
+/// Case patterns matching the subcommand paths and, since groups can be
+/// given several times, anything after them
+fn group_patterns(paths: &[String]) -> String {
+    paths
+        .iter()
+        .flat_map(|p| [format!("\"{}\"", p), format!("\"{} \"*", p)])
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Completes task ids and groups by asking `tdlist __complete` for them
+/// each time
+fn dynamic_completions(shell: Shell, found: &DynamicArgs) -> String {
+    let ids = found
+        .ids
+        .iter()
+        .map(|p| format!("\"{}\"", p))
+        .collect::<Vec<_>>();
+    match shell {
+        Shell::Bash => BASH
+            .replace("@IDS@", &ids.join("|"))
+            .replace("@GROUPS@", &group_patterns(&found.groups)),
+        Shell::Zsh => ZSH
+            .replace("@IDS@", &ids.join("|"))
+            .replace("@GROUPS@", &group_patterns(&found.groups)),
+        Shell::Fish => {
+            // Uses the helper clap_complete defines to match the first
+            // subcommand exactly
+            let condition = |path: &String| {
+                let mut names = path.split(' ');
+                let first = names.next().unwrap_or_default();
+                std::iter::once(format!("__fish_tdlist_using_subcommand {}", first))
+                    .chain(names.map(|name| format!("__fish_seen_subcommand_from {}", name)))
+                    .collect::<Vec<_>>()
+                    .join("; and ")
+            };
+            let mut lines = vec![
+                String::new(),
+                "complete -c tdlist -l group -r -f -a '(tdlist __complete groups)'".to_string(),
+            ];
+            for path in &found.ids {
+                lines.push(format!(
+                    "complete -c tdlist -n '{}' -f -a '(tdlist __complete ids)'",
+                    condition(path)
+                ));
+            }
+            for path in &found.groups {
+                lines.push(format!(
+                    "complete -c tdlist -n '{}' -f -a '(tdlist __complete groups)'",
+                    condition(path)
+                ));
+            }
+            lines.join("\n") + "\n"
+        }
+        _ => String::new(),
+    }
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let mut cmd = super::command(&app.settings);
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(args.shell, &mut cmd, name, &mut io::stdout());
+
+    let mut found = DynamicArgs::default();
+    find_dynamic_args(&cmd, &mut vec![], &mut found);
+    print!("{}", dynamic_completions(args.shell, &found));
+    Ok(())
+}
+
+/// Lists what completion scripts offer for task ids and groups
+pub fn run_helper(app: App, args: HelperArgs) -> Result<()> {
+    match args.candidates {
+        Candidates::Ids => {
+            let mut tasks: Vec<_> = app.tasks.values().collect();
+            tasks.sort_by_key(|t| t.id);
+            for task in tasks {
+                println!("{}\t{}", task.id.unwrap_or_default(), task.name);
+            }
+        }
+        Candidates::Groups => {
+            let groups = app.tasks.values().filter_map(|t| t.group.as_deref());
+            for group in group::hierarchy(groups) {
+                println!("{}", group);
+            }
+        }
+    }
+    Ok(())
+}
@@ -6,7 +6,7 @@ use clap::Parser;
 #[derive(Parser)]
 pub struct Args {}
 
-pub fn run(app: App, args: Args) -> Result<()> {
+pub fn run(_app: App, _args: Args) -> Result<()> {
     // TODO:
     Ok(())
 }
@@ -0,0 +1,45 @@
+use crate::app::App;
+use crate::group;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: ContextCommand,
+}
+
+#[derive(Subcommand)]
+enum ContextCommand {
+    /// Works in a group: new tasks go into it and `ls` only shows it
+    Set {
+        /// The group to work in
+        group: String,
+    },
+    /// Works across all groups again
+    Clear,
+    /// Shows the group being worked in
+    Show,
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    match args.command {
+        ContextCommand::Set { group } => {
+            let group =
+                group::normalize(&group).ok_or_else(|| anyhow!("Group name can't be empty"))?;
+            println!("Context: {}", group);
+            app.settings.current_group = Some(group);
+            app.settings.save_to_file()?;
+        }
+        ContextCommand::Clear => {
+            app.settings.current_group = None;
+            app.settings.save_to_file()?;
+        }
+        ContextCommand::Show => match &app.settings.current_group {
+            Some(group) => println!("Context: {}", group),
+            None => println!("No context set"),
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use crate::app::{App, Id};
+use crate::group;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+
+use super::formats::Format;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: GroupCommand,
+}
+
+#[derive(Subcommand)]
+enum GroupCommand {
+    /// Lists every group as a tree, with how many tasks are in it and the
+    /// groups nested under it
+    List {
+        /// The format to print groups with
+        #[arg(short, long)]
+        format: Option<Format>,
+    },
+    /// Renames a group on every task in it or nested under it
+    Rename {
+        /// The group to rename
+        from: String,
+        /// The new name, which must not be in use
+        to: String,
+    },
+    /// Moves every task in the given groups into another group, keeping
+    /// nested groups nested
+    Merge {
+        /// The groups to merge
+        #[arg(required = true)]
+        groups: Vec<String>,
+        /// The group to merge them into
+        #[arg(long)]
+        into: String,
+    },
+    /// Hides a group and the groups nested under it from `ls` unless it's
+    /// asked for by name
+    Archive {
+        /// The group to archive
+        group: String,
+    },
+    /// Shows an archived group in `ls` again
+    Unarchive {
+        /// The group to unarchive
+        group: String,
+    },
+}
+
+#[derive(Serialize)]
+struct GroupSummary {
+    group: String,
+    open: usize,
+    total: usize,
+    archived: bool,
+}
+
+fn parse_group(group: &str) -> Result<String> {
+    group::normalize(group).ok_or_else(|| anyhow!("Group name can't be empty"))
+}
+
+fn tasks_in_group(app: &App, group: &str) -> Vec<Id> {
+    app.tasks
+        .iter()
+        .filter(|(_, t)| t.in_group(group))
+        .map(|(&id, _)| id)
+        .collect()
+}
+
+/// Moves the tasks in `from`, and in groups nested under it, to the same
+/// place under `to`. Nothing is saved until every group has been moved.
+fn regroup(app: &mut App, from: &str, to: &str) -> Result<usize> {
+    let ids = tasks_in_group(app, from);
+    for &id in &ids {
+        let task = app.get_task_mut(id)?;
+        let moved = task
+            .group
+            .as_deref()
+            .and_then(group::normalize)
+            .and_then(|g| group::reparent(&g, from, to));
+        if let Some(moved) = moved {
+            task.set_group(moved);
+        }
+    }
+    Ok(ids.len())
+}
+
+/// Points settings that name a group under `from` at `to` instead, returning
+/// whether anything changed
+fn regroup_settings(app: &mut App, from: &str, to: &str) -> bool {
+    let settings = &mut app.settings;
+    let mut changed = false;
+    let current = settings.current_group.as_deref().and_then(group::normalize);
+    if let Some(moved) = current.and_then(|g| group::reparent(&g, from, to)) {
+        settings.current_group = Some(moved);
+        changed = true;
+    }
+    for archived in settings.archived_groups.iter_mut() {
+        if let Some(moved) = group::reparent(archived, from, to) {
+            *archived = moved;
+            changed = true;
+        }
+    }
+    settings.archived_groups.sort();
+    settings.archived_groups.dedup();
+    changed
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    match args.command {
+        GroupCommand::List { format } => {
+            let stored: Vec<String> = app
+                .tasks
+                .values()
+                .filter_map(|t| t.group.as_deref().and_then(group::normalize))
+                .collect();
+            let groups: Vec<GroupSummary> = group::hierarchy(stored.iter().map(String::as_str))
+                .into_iter()
+                .map(|name| {
+                    let tasks = app.tasks.values().filter(|t| t.in_group(&name));
+                    let (open, total) = tasks.fold((0, 0), |(open, total), t| {
+                        (open + usize::from(!t.complete), total + 1)
+                    });
+                    let archived = app
+                        .settings
+                        .archived_groups
+                        .iter()
+                        .any(|a| group::is_within(&name, a));
+                    GroupSummary {
+                        group: name,
+                        open,
+                        total,
+                        archived,
+                    }
+                })
+                .collect();
+
+            match format {
+                Some(Format::Json) => println!(
+                    "{}",
+                    serde_json::to_string(&groups).expect("Failed to serialize groups to json")
+                ),
+                Some(Format::JsonPretty) => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&groups)
+                        .expect("Failed to serialize groups to json")
+                ),
+                _ => {
+                    // Nested groups are indented under their parent by name
+                    let label = |g: &GroupSummary| {
+                        format!(
+                            "{}{}",
+                            "  ".repeat(group::depth(&g.group)),
+                            group::name(&g.group)
+                        )
+                    };
+                    let longest = groups.iter().map(|g| label(g).len()).max().unwrap_or(0);
+                    for summary in &groups {
+                        let archived = if summary.archived { " (archived)" } else { "" };
+                        println!(
+                            "{:width$}  {} open, {} total{}",
+                            label(summary),
+                            summary.open,
+                            summary.total,
+                            archived,
+                            width = longest
+                        );
+                    }
+                }
+            }
+        }
+        GroupCommand::Rename { from, to } => {
+            let from = parse_group(&from)?;
+            let to = parse_group(&to)?;
+            if tasks_in_group(&app, &from).is_empty() {
+                return Err(anyhow!("Group {} not found", from));
+            }
+            // Tasks being renamed don't count, e.g. moving work/backend up
+            // to work when nothing else is in work
+            let taken = app
+                .tasks
+                .values()
+                .any(|t| t.in_group(&to) && !t.in_group(&from));
+            if from != to && taken {
+                return Err(anyhow!(
+                    "Group {} already exists, use `group merge` to combine them",
+                    to
+                ));
+            }
+            let count = regroup(&mut app, &from, &to)?;
+            app.save_state();
+            if regroup_settings(&mut app, &from, &to) {
+                app.settings.save_to_file()?;
+            }
+            println!("Renamed {} to {} on {} tasks", from, to, count);
+        }
+        GroupCommand::Merge { groups, into } => {
+            let into = parse_group(&into)?;
+            let groups = groups
+                .iter()
+                .map(|g| parse_group(g))
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(missing) = groups.iter().find(|g| tasks_in_group(&app, g).is_empty()) {
+                return Err(anyhow!("Group {} not found", missing));
+            }
+            let mut count = 0;
+            let mut settings_changed = false;
+            for from in &groups {
+                count += regroup(&mut app, from, &into)?;
+                settings_changed |= regroup_settings(&mut app, from, &into);
+            }
+            app.save_state();
+            if settings_changed {
+                app.settings.save_to_file()?;
+            }
+            println!("Moved {} tasks into {}", count, into);
+        }
+        GroupCommand::Archive { group } => {
+            let group = parse_group(&group)?;
+            if tasks_in_group(&app, &group).is_empty() {
+                return Err(anyhow!("Group {} not found", group));
+            }
+            if !app.settings.archived_groups.contains(&group) {
+                app.settings.archived_groups.push(group);
+                app.settings.save_to_file()?;
+            }
+        }
+        GroupCommand::Unarchive { group } => {
+            let group = parse_group(&group)?;
+            if !app.settings.archived_groups.contains(&group) {
+                return Err(anyhow!("Group {} isn't archived", group));
+            }
+            app.settings.archived_groups.retain(|g| *g != group);
+            app.settings.save_to_file()?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use crate::configuration::Settings;
+use crate::repeat::REPEAT_HELP;
+
+use clap::Command;
+
+/// How dates are written, using the formats configured in settings
+fn date_help(settings: &Settings) -> String {
+    let formats = &settings.date_formats;
+    format!(
+        "\
+Dates are written as {}, or as {} with a time.
+Most commands also take today, tomorrow, a weekday such as friday or next
+friday, or an offset from today such as +3d or +2w.",
+        formats.input_date_hint, formats.input_datetime_hint
+    )
+}
+
+/// Adds sections to the long help of a subcommand, after its short about
+fn with_sections(cmd: Command, sections: &[&str]) -> Command {
+    let about = cmd.get_about().map(|a| a.to_string()).unwrap_or_default();
+    cmd.long_about(format!("{}\n\n{}", about, sections.join("\n\n")))
+}
+
+const ADD_EXAMPLES: &str = "\
+Examples:
+  tdlist add \"Pay rent\" --date tomorrow --repeats monthly
+  tdlist add Standup --date monday --repeats mon,tue,wed,thu,fri
+  tdlist add \"Water plants\" --repeats \"every 3 days after completion\"";
+
+const BULK_EXAMPLES: &str = "\
+Examples:
+  tdlist bulk --filter \"--group work --date-filter past\" shift-date +1w --dry-run
+  tdlist bulk --filter \"--group home\" set group=home/garden repeats=weekly
+  tdlist bulk --filter \"--date today\" complete";
+
+/// Fills in the parts of the help that depend on settings
+pub fn document(cmd: Command, settings: &Settings) -> Command {
+    let dates = date_help(settings);
+    let dates = dates.as_str();
+    cmd.mut_subcommand("add", |c| {
+        with_sections(c, &[dates, REPEAT_HELP, ADD_EXAMPLES])
+    })
+    .mut_subcommand("bulk", |c| {
+        with_sections(c, &[dates, REPEAT_HELP, BULK_EXAMPLES])
+    })
+    .mut_subcommand("ls", |c| with_sections(c, &[dates]))
+    .mut_subcommand("postpone", |c| with_sections(c, &[dates]))
+    .mut_subcommand("agenda", |c| with_sections(c, &[dates]))
+    .mut_subcommand("cal", |c| with_sections(c, &[dates]))
+}
@@ -0,0 +1,49 @@
+use crate::app::{App, Id};
+use crate::utils;
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+
+use super::formats::Format;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the task
+    id: Id,
+    /// The format to print the history with
+    #[arg(short, long)]
+    format: Option<Format>,
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let Args { id, format } = args;
+    let task = app
+        .get_task(id)
+        .ok_or_else(|| anyhow!("Task {} not found", id))?;
+
+    match format {
+        Some(Format::Json) => println!(
+            "{}",
+            serde_json::to_string(&task.history).expect("Failed to serialize history to json")
+        ),
+        Some(Format::JsonPretty) => println!(
+            "{}",
+            serde_json::to_string_pretty(&task.history)
+                .expect("Failed to serialize history to json")
+        ),
+        _ => {
+            let settings = &app.settings;
+            println!("{} ({})", task.name, id);
+            if task.history.is_empty() {
+                println!("No history yet");
+            }
+            for entry in &task.history {
+                let at = utils::to_local(&entry.at)
+                    .format(&settings.date_formats.display_datetime_format);
+                let due = utils::date_to_display_str(&entry.due, settings);
+                println!("{:9}  {}  (due {})", entry.kind, at, due);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::app::{App, Id};
+use crate::configuration::get_attachments_dir;
+use crate::link::{Link, LinkTarget};
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: LinkCommand,
+}
+
+#[derive(Subcommand)]
+enum LinkCommand {
+    /// Lists the links and attachments of a task
+    List {
+        /// The id of the task
+        id: Id,
+    },
+    /// Adds a link to a task
+    Add {
+        /// The id of the task
+        id: Id,
+        /// The link as label=url or a bare url
+        link: String,
+    },
+    /// Attaches a local file to a task
+    Attach {
+        /// The id of the task
+        id: Id,
+        /// The file to attach
+        path: PathBuf,
+        /// A label for the attachment
+        #[arg(long)]
+        label: Option<String>,
+        /// Copy the file into the data directory instead of referencing it
+        #[arg(long)]
+        copy: bool,
+    },
+    /// Removes a link or attachment from a task
+    Remove {
+        /// The id of the task
+        id: Id,
+        /// The number of the link, as shown by `link list`
+        index: usize,
+    },
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    match args.command {
+        LinkCommand::List { id } => {
+            let task = app
+                .get_task(id)
+                .ok_or_else(|| anyhow!("Task {} not found", id))?;
+            for (i, link) in task.links.iter().enumerate() {
+                let kind = match link.target {
+                    LinkTarget::Url(_) => "url",
+                    LinkTarget::File(_) => "file",
+                };
+                println!("{}: [{}] {}", i + 1, kind, link);
+            }
+        }
+        LinkCommand::Add { id, link } => {
+            let link = Link::parse(&link)?;
+            app.get_task_mut(id)?.add_link(link);
+            app.save_state();
+        }
+        LinkCommand::Attach {
+            id,
+            path,
+            label,
+            copy,
+        } => {
+            if app.get_task(id).is_none() {
+                return Err(anyhow!("Task {} not found", id));
+            }
+            let link = if copy {
+                let dir = get_attachments_dir().join(id.to_string());
+                Link::copy_file(&path, label, &dir)?
+            } else {
+                Link::file(&path, label)?
+            };
+            app.get_task_mut(id)?.add_link(link);
+            app.save_state();
+        }
+        LinkCommand::Remove { id, index } => {
+            let task = app.get_task_mut(id)?;
+            if index == 0 || index > task.links.len() {
+                return Err(anyhow!("Task {} has no link {}", id, index));
+            }
+            let link = task.links.remove(index - 1);
+            // Copies belong to the task, so they go with it unless another
+            // link still points at the same file
+            let archive = app.load_archive();
+            let shared = app
+                .tasks
+                .values()
+                .chain(archive.values())
+                .flat_map(|t| &t.links)
+                .any(|l| l.same_file(&link));
+            if link.is_stored_in(&get_attachments_dir()) && !shared {
+                fs::remove_file(link.target_str())?;
+            }
+            app.save_state();
+        }
+    }
+    Ok(())
+}
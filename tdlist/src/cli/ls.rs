@@ -2,10 +2,12 @@ use std::collections::HashMap;
 
 use crate::app::{App, Id};
 use crate::configuration::Settings;
+use crate::due_date::DueDate;
 use crate::task::Task;
 use crate::utils;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use clap::{Parser, ValueEnum};
 
 use super::cli_utils;
@@ -16,24 +18,60 @@ pub struct Args {
     /// The format to print tasks with
     #[arg(short, long)]
     format: Option<Format>,
-    /// Whether to show complete tasks
-    #[arg(short, long)]
-    show_complete: bool,
     /// Whether to show task descriptions
     #[arg(long)]
     show_descriptions: bool,
     /// Whether to show task urls
     #[arg(long)]
     show_urls: bool,
+    #[command(flatten)]
+    filters: Filters,
+    /// List archived tasks instead
+    #[arg(long)]
+    archived: bool,
+    /// What to sort tasks by
+    #[arg(long, value_enum, default_value_t = SortBy::Date)]
+    sort: SortBy,
+}
+
+/// The ways of narrowing down tasks, shared by every command that picks
+/// tasks the way `ls` does
+#[derive(Parser)]
+pub struct Filters {
+    /// Whether to show complete tasks
+    #[arg(short, long)]
+    show_complete: bool,
     /// Filter tasks by relative date
     #[arg(long)]
     date_filter: Option<DateFilter>,
     /// Filter tasks by date
     #[arg(long)]
     date: Option<String>,
-    /// Filter by group
+    /// Filter tasks by relative scheduled (start) date
+    #[arg(long)]
+    scheduled: Option<DateFilter>,
+    /// Include tasks whose scheduled date hasn't arrived yet
+    #[arg(short, long)]
+    all: bool,
+    /// Filter by group, including the groups nested under it. Defaults to
+    /// the current context.
     #[arg(long)]
     group: Option<String>,
+    /// Show every group, ignoring the current context
+    #[arg(long, conflicts_with = "group")]
+    all_groups: bool,
+    /// Only show tasks completed on or after this date
+    #[arg(long)]
+    completed_since: Option<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum SortBy {
+    Date,
+    Name,
+    Created,
+    Modified,
+    Completed,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -45,36 +83,63 @@ pub enum DateFilter {
     Next24,
 }
 
+fn matches_relative_date(date: &DueDate, date_filter: DateFilter) -> bool {
+    let now = chrono::Utc::now();
+    let today = chrono::Local::now().date_naive();
+    match date_filter {
+        DateFilter::All => true,
+        DateFilter::Today => date.local_date() == today,
+        DateFilter::Past => date.deadline() < now,
+        DateFilter::TodayAndPast => date.local_date() <= today,
+        DateFilter::Next24 => {
+            let tomorrow = now + chrono::Duration::days(1);
+            let deadline = date.deadline();
+            deadline >= now && deadline < tomorrow
+        }
+    }
+}
+
 pub fn filter_by_relative_date(
     tasks: HashMap<Id, Task>,
     date_filter: Option<DateFilter>,
 ) -> HashMap<Id, Task> {
-    let now = chrono::Local::now();
     match date_filter {
-        Some(DateFilter::Today) => tasks
+        Some(date_filter) => tasks
             .into_iter()
-            .filter(|(_, t)| {
-                let today = now.date_naive();
-                t.date.date_naive() == today
-            })
-            .collect(),
-        Some(DateFilter::Past) => tasks.into_iter().filter(|(_, t)| t.date < now).collect(),
-        Some(DateFilter::TodayAndPast) => tasks
-            .into_iter()
-            .filter(|(_, t)| {
-                let today = now.date_naive();
-                t.date.date_naive() <= today
-            })
+            .filter(|(_, t)| matches_relative_date(&t.date, date_filter))
             .collect(),
-        Some(DateFilter::Next24) => tasks
+        None => tasks,
+    }
+}
+
+pub fn filter_by_scheduled(
+    tasks: HashMap<Id, Task>,
+    scheduled: Option<DateFilter>,
+) -> HashMap<Id, Task> {
+    match scheduled {
+        Some(scheduled) => tasks
             .into_iter()
             .filter(|(_, t)| {
-                let tomorrow = now + chrono::Duration::days(1);
-                t.date >= now && t.date < tomorrow
+                t.scheduled
+                    .as_ref()
+                    .map(|d| matches_relative_date(d, scheduled))
+                    .unwrap_or(false)
             })
             .collect(),
-        _ => tasks,
+        None => tasks,
+    }
+}
+
+/// Drops tasks whose scheduled date hasn't arrived yet, unless `show_all`
+pub fn filter_by_started(tasks: HashMap<Id, Task>, show_all: bool) -> HashMap<Id, Task> {
+    if show_all {
+        return tasks;
     }
+    let now = chrono::Utc::now();
+    tasks
+        .into_iter()
+        .filter(|(_, t)| t.scheduled.map(|d| d.has_arrived(now)).unwrap_or(true))
+        .collect()
 }
 
 pub fn filter_by_exact_date(
@@ -84,8 +149,15 @@ pub fn filter_by_exact_date(
 ) -> Result<HashMap<Id, Task>> {
     let tasks = match date {
         Some(date) => {
-            let date = utils::parse_date(date.as_str(), settings)?;
-            tasks.into_iter().filter(|(_, t)| t.date == date).collect()
+            let date = utils::parse_relative_date(&date, settings)?;
+            tasks
+                .into_iter()
+                .filter(|(_, t)| match date {
+                    // A date-only filter matches everything due that day
+                    DueDate::Date(day) => t.date.local_date() == day,
+                    DueDate::DateTime(_) => t.date == date,
+                })
+                .collect()
         }
         None => tasks,
     };
@@ -94,46 +166,129 @@ pub fn filter_by_exact_date(
 
 pub fn filter_by_group(tasks: HashMap<Id, Task>, group: Option<String>) -> HashMap<Id, Task> {
     match group {
-        Some(group) => {
-            let group = group.to_lowercase();
+        Some(group) => tasks
+            .into_iter()
+            .filter(|(_, t)| t.in_group(&group))
+            .collect(),
+        None => tasks,
+    }
+}
+
+/// Drops tasks in archived groups
+pub fn filter_archived_groups(tasks: HashMap<Id, Task>, archived: &[String]) -> HashMap<Id, Task> {
+    tasks
+        .into_iter()
+        .filter(|(_, t)| !archived.iter().any(|g| t.in_group(g)))
+        .collect()
+}
+
+pub fn filter_by_completed_since(
+    tasks: HashMap<Id, Task>,
+    completed_since: Option<String>,
+    settings: &Settings,
+) -> Result<HashMap<Id, Task>> {
+    let tasks = match completed_since {
+        Some(date) => {
+            let since = utils::parse_relative_date(&date, settings)?.start();
             tasks
                 .into_iter()
-                .filter(|(_, t)| {
-                    t.group
-                        .as_ref()
-                        .map(|g| g.to_lowercase() == group)
-                        .unwrap_or(false)
-                })
+                .filter(|(_, t)| t.completed_at.map(|c| c >= since).unwrap_or(false))
                 .collect()
         }
         None => tasks,
+    };
+    Ok(tasks)
+}
+
+/// Sorts tasks by `sort_by`, most recent first for timestamps, with tasks
+/// lacking the timestamp at the end
+pub fn sort_tasks(tasks: &mut [&Task], sort_by: SortBy) {
+    let by_date = |a: &&Task, b: &&Task| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name));
+    let by_timestamp = |a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>| match (a, b) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    };
+    match sort_by {
+        SortBy::Date => tasks.sort_by(by_date),
+        SortBy::Name => tasks.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| by_date(a, b))),
+        SortBy::Created => tasks
+            .sort_by(|a, b| by_timestamp(a.created_at, b.created_at).then_with(|| by_date(a, b))),
+        SortBy::Modified => tasks
+            .sort_by(|a, b| by_timestamp(a.modified_at, b.modified_at).then_with(|| by_date(a, b))),
+        SortBy::Completed => tasks.sort_by(|a, b| {
+            by_timestamp(a.completed_at, b.completed_at).then_with(|| by_date(a, b))
+        }),
+    }
+}
+
+impl Filters {
+    /// The current context, when no group was asked for
+    pub fn context(&self, settings: &Settings) -> Option<String> {
+        match (&self.group, self.all_groups) {
+            (None, false) => settings.current_group.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, tasks: HashMap<Id, Task>, settings: &Settings) -> Result<HashMap<Id, Task>> {
+        let context = self.context(settings);
+        let Filters {
+            show_complete,
+            date_filter,
+            date,
+            scheduled,
+            all,
+            group,
+            all_groups: _,
+            completed_since,
+        } = self;
+
+        // Asking for completed tasks implies showing them
+        let show_complete = show_complete || completed_since.is_some();
+        let tasks: HashMap<Id, Task> = if !show_complete {
+            tasks.into_iter().filter(|(_, t)| !t.complete).collect()
+        } else {
+            tasks
+        };
+
+        let tasks = filter_by_relative_date(tasks, date_filter);
+        let tasks = filter_by_exact_date(tasks, date, settings)?;
+        let tasks = filter_by_scheduled(tasks, scheduled);
+        let tasks = filter_by_started(tasks, all);
+        // Archived groups only show up when asked for by name
+        let tasks = match group.or(context) {
+            Some(group) => filter_by_group(tasks, Some(group)),
+            None => filter_archived_groups(tasks, &settings.archived_groups),
+        };
+        filter_by_completed_since(tasks, completed_since, settings)
     }
 }
 
 pub fn run(app: App, args: Args) -> Result<()> {
     let Args {
         format,
-        show_complete,
         show_descriptions,
         show_urls,
-        date_filter,
-        date,
-        group,
+        mut filters,
+        archived,
+        sort,
     } = args;
 
-    let tasks: HashMap<Id, Task> = if !show_complete {
-        app.tasks.into_iter().filter(|(_, t)| !t.complete).collect()
+    filters.show_complete |= archived;
+    let context = filters.context(&app.settings);
+    let tasks = if archived {
+        app.load_archive()
     } else {
         app.tasks
     };
-
-    let tasks = filter_by_relative_date(tasks, date_filter);
-    let tasks = filter_by_exact_date(tasks, date, &app.settings)?;
-    let tasks = filter_by_group(tasks, group);
+    let tasks = filters.apply(tasks, &app.settings)?;
 
     let mut tasks_vec = tasks.values().collect::<Vec<_>>();
-    tasks_vec.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+    sort_tasks(&mut tasks_vec, sort);
 
+    if let (Some(context), None | Some(Format::PlainText)) = (&context, format) {
+        println!("Context: {}", context);
+    }
     cli_utils::print_tasks(
         tasks_vec,
         format,
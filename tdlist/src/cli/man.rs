@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app::App;
+
+use anyhow::Result;
+use clap::{Command, Parser};
+use clap_mangen::Man;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The directory to write the pages to
+    #[arg(long, default_value = "man")]
+    out_dir: PathBuf,
+}
+
+/// Writes a page for `cmd` and each of its subcommands. Once built, clap
+/// names subcommands after their parents, e.g. tdlist-group-rename.
+fn write_pages(cmd: &Command, dir: &Path) -> Result<usize> {
+    let name = cmd.get_display_name().unwrap_or(cmd.get_name());
+    let mut buffer = Vec::new();
+    Man::new(cmd.clone()).render(&mut buffer)?;
+    fs::write(dir.join(format!("{}.1", name)), buffer)?;
+
+    let mut count = 1;
+    for sub in cmd.get_subcommands() {
+        if sub.is_hide_set() || sub.get_name() == "help" {
+            continue;
+        }
+        count += write_pages(sub, dir)?;
+    }
+    Ok(count)
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let mut cmd = super::command(&app.settings);
+    cmd.build();
+    fs::create_dir_all(&args.out_dir)?;
+    let count = write_pages(&cmd, &args.out_dir)?;
+    println!("Wrote {} man pages to {}", count, args.out_dir.display());
+    Ok(())
+}
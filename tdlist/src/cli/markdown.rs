@@ -0,0 +1,135 @@
+use crate::configuration::{Color, Settings};
+
+use crossterm::style::{StyledContent, Stylize};
+
+/// Renders the subset of Markdown used in task notes for the terminal:
+/// headings, lists, quotes, fenced code blocks and inline code, bold and
+/// italics. Without `styled` the structure is kept but no escape codes are
+/// written.
+pub fn render(text: &str, settings: &Settings, styled: bool) -> String {
+    let accent = Color::from_tui_color(settings.colors.accent_color).to_crossterm_color();
+    let code = Color::from_tui_color(settings.colors.secondary_color).to_crossterm_color();
+    let paint = |s: StyledContent<String>| {
+        if styled {
+            s.to_string()
+        } else {
+            s.content().clone()
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            lines.push(format!("    {}", paint(line.to_string().with(code))));
+            continue;
+        }
+
+        let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            let heading = trimmed[hashes..].trim().to_string();
+            let heading = match hashes {
+                1 => heading.to_uppercase().with(accent).bold().underlined(),
+                _ => heading.with(accent).bold(),
+            };
+            lines.push(paint(heading));
+            continue;
+        }
+
+        if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            let item = render_inline(item, code, styled);
+            lines.push(format!("{}  • {}", indent, item));
+            continue;
+        }
+
+        let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 && trimmed[digits..].starts_with(". ") {
+            let item = render_inline(&trimmed[digits + 2..], code, styled);
+            lines.push(format!("{}  {}. {}", indent, &trimmed[..digits], item));
+            continue;
+        }
+
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            let quote = render_inline(quote.trim_start(), code, styled);
+            lines.push(format!("{}│ {}", indent, quote));
+            continue;
+        }
+
+        lines.push(format!(
+            "{}{}",
+            indent,
+            render_inline(trimmed, code, styled)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Span {
+    Code,
+    Bold,
+    Italic,
+}
+
+/// Styles `inline code`, **bold** and *italic* or _italic_ runs
+fn render_inline(text: &str, code: crossterm::style::Color, styled: bool) -> String {
+    let mut out = String::new();
+    let mut run = String::new();
+    let mut span: Option<Span> = None;
+    let mut chars = text.chars().peekable();
+
+    let flush = |out: &mut String, run: &mut String, span: Option<Span>| {
+        let content = std::mem::take(run);
+        if !styled {
+            out.push_str(&content);
+            return;
+        }
+        let content = match span {
+            Some(Span::Code) => content.with(code).to_string(),
+            Some(Span::Bold) => content.bold().to_string(),
+            Some(Span::Italic) => content.italic().to_string(),
+            None => content,
+        };
+        out.push_str(&content);
+    };
+
+    let mut prev: Option<char> = None;
+    while let Some(c) = chars.next() {
+        // Underscores inside words, as in snake_case, are left alone
+        let in_word = prev.is_some_and(|p| p.is_alphanumeric())
+            && chars.peek().is_some_and(|n| n.is_alphanumeric());
+        let (marker, text) = match (c, span) {
+            ('`', None) | ('`', Some(Span::Code)) => (Some(Span::Code), "`"),
+            (_, Some(Span::Code)) => (None, ""),
+            ('*', _) if chars.peek() == Some(&'*') => {
+                chars.next();
+                (Some(Span::Bold), "**")
+            }
+            ('*', _) => (Some(Span::Italic), "*"),
+            ('_', _) if !in_word => (Some(Span::Italic), "_"),
+            _ => (None, ""),
+        };
+        prev = Some(c);
+        match marker {
+            // Close the current span, or open a new one if none is open
+            Some(m) if span == Some(m) || span.is_none() => {
+                flush(&mut out, &mut run, span);
+                span = if span == Some(m) { None } else { Some(m) };
+            }
+            Some(_) => run.push_str(text),
+            None => run.push(c),
+        }
+    }
+    flush(&mut out, &mut run, span);
+    out
+}
@@ -1,14 +1,38 @@
 use crate::app::App;
+use crate::configuration::Settings;
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 
 mod add;
+mod agenda;
+mod archive;
+mod bulk;
+mod cal;
 mod cli_utils;
 mod complete;
+mod completions;
 mod config;
+mod context;
 mod delete;
 mod formats;
+mod group;
+mod help;
+mod history;
+mod link;
 mod ls;
+mod man;
+mod markdown;
+mod note;
+mod notify;
+mod open;
+mod postpone;
+mod report;
+mod show;
+mod skip;
+mod snooze;
+mod stats;
+mod timer;
+mod undo;
 
 #[derive(Parser)]
 struct Args {
@@ -21,22 +45,142 @@ enum Command {
     /// Lists all the tasks
     Ls(ls::Args),
     /// Adds a task to your todos
-    Add(add::Args),
+    Add(Box<add::Args>),
+    /// Shows every detail of a task
+    Show(show::Args),
+    /// Manages the links and attachments of a task
+    Link(link::Args),
+    /// Opens the links of a task
+    Open(open::Args),
+    /// Edits the notes of a task in $EDITOR
+    Note(note::Args),
     /// Deletes a task from your todos
     Delete(delete::Args),
     /// Marks a task as complete or incomplete
     Complete(complete::Args),
+    /// Changes every task matching a filter at once
+    Bulk(bulk::Args),
+    /// Reverts the last bulk change
+    Undo(undo::Args),
+    /// Moves completed tasks out of the task list into the archive
+    Archive(archive::Args),
+    /// Moves a task out of the archive
+    Unarchive(archive::UnarchiveArgs),
+    /// Pushes a task out by a duration
+    Snooze(snooze::Args),
+    /// Moves a task to another day
+    Postpone(postpone::Args),
+    /// Moves a repeating task to its next occurrence without completing it
+    Skip(skip::Args),
     /// Sets default configurations
     Config(config::Args),
+    /// Lists tasks and upcoming repeats day by day
+    Agenda(agenda::Args),
+    /// Shows tasks on a week or month calendar
+    Cal(cal::Args),
+    /// Shows when a task was completed or skipped
+    History(history::Args),
+    /// Sets the group new tasks go into and `ls` shows
+    Context(context::Args),
+    /// Lists, renames, merges and archives groups
+    Group(group::Args),
+    /// Starts logging time on a task, stopping any other timer
+    Start(timer::StartArgs),
+    /// Stops logging time
+    Stop(timer::StopArgs),
+    /// Summarises logged time
+    Report(report::Args),
+    /// Shows completion statistics
+    Stats(stats::Args),
+    /// Sends reminders that are due, once or on an interval
+    Notify(notify::Args),
+    /// Prints a shell completion script
+    ///
+    /// Source it from your shell's startup file, e.g. `source <(tdlist
+    /// completions bash)`, so task ids and groups are looked up as you type.
+    Completions(completions::Args),
+    /// Writes man pages for tdlist and every subcommand
+    Man(man::Args),
+    /// Lists task ids or groups for completion scripts
+    #[command(name = "__complete", hide = true)]
+    CompletionHelper(completions::HelperArgs),
+    /// Keeps running in the background, sending reminders as they come due
+    Daemon(notify::DaemonArgs),
+}
+
+impl Command {
+    /// Whether the command edits tasks, and so may as well archive old ones
+    /// once it's done. Reading, shell completion and undo leave the files
+    /// alone, and so does bulk, so that its changes can still be undone.
+    fn changes_tasks(&self) -> bool {
+        matches!(
+            self,
+            Command::Add(_)
+                | Command::Link(_)
+                | Command::Note(_)
+                | Command::Delete(_)
+                | Command::Complete(_)
+                | Command::Snooze(_)
+                | Command::Postpone(_)
+                | Command::Skip(_)
+                | Command::Group(_)
+                | Command::Start(_)
+                | Command::Stop(_)
+        )
+    }
+}
+
+/// The clap command tree behind the CLI, with help that depends on the
+/// settings filled in
+pub fn command(settings: &Settings) -> clap::Command {
+    help::document(Args::command(), settings)
 }
 
 pub fn start_cli(app: App) -> Result<()> {
-    let args = Args::parse();
-    match args.command {
+    let matches = command(&app.settings).get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let changes_tasks = args.command.changes_tasks();
+    let settings = app.settings.clone();
+    run_command(app, args.command)?;
+    // Archiving afterwards means the task a command names is still there
+    // for it, even if it's old enough to be archived
+    if changes_tasks {
+        App::new(settings).auto_archive()?;
+    }
+    Ok(())
+}
+
+fn run_command(app: App, command: Command) -> Result<()> {
+    match command {
         Command::Ls(args) => ls::run(app, args),
-        Command::Add(args) => add::run(app, args),
+        Command::Add(args) => add::run(app, *args),
+        Command::Show(args) => show::run(app, args),
+        Command::Link(args) => link::run(app, args),
+        Command::Open(args) => open::run(app, args),
+        Command::Note(args) => note::run(app, args),
         Command::Delete(args) => delete::run(app, args),
         Command::Complete(args) => complete::run(app, args),
+        Command::Bulk(args) => bulk::run(app, args),
+        Command::Undo(args) => undo::run(app, args),
+        Command::Archive(args) => archive::run(app, args),
+        Command::Unarchive(args) => archive::run_unarchive(app, args),
+        Command::Snooze(args) => snooze::run(app, args),
+        Command::Postpone(args) => postpone::run(app, args),
+        Command::Skip(args) => skip::run(app, args),
         Command::Config(args) => config::run(app, args),
+        Command::Agenda(args) => agenda::run(app, args),
+        Command::Cal(args) => cal::run(app, args),
+        Command::History(args) => history::run(app, args),
+        Command::Context(args) => context::run(app, args),
+        Command::Group(args) => group::run(app, args),
+        Command::Start(args) => timer::run_start(app, args),
+        Command::Stop(args) => timer::run_stop(app, args),
+        Command::Report(args) => report::run(app, args),
+        Command::Stats(args) => stats::run(app, args),
+        Command::Notify(args) => notify::run(app, args),
+        Command::Daemon(args) => notify::run_daemon(app, args),
+        Command::Completions(args) => completions::run(app, args),
+        Command::Man(args) => man::run(app, args),
+        Command::CompletionHelper(args) => completions::run_helper(app, args),
     }
 }
@@ -0,0 +1,83 @@
+use crate::app::{App, Id};
+
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, process};
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the task to edit the notes of
+    id: Id,
+}
+
+/// The editor from `$VISUAL` or `$EDITOR`, which may include arguments such
+/// as `code --wait`
+fn editor() -> Vec<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or("vi".to_string());
+    editor.split_whitespace().map(String::from).collect()
+}
+
+/// A new directory only the current user can use. Creating it fails if
+/// anything, including a symlink, is already at the path, so other users
+/// can't redirect what's written into it.
+fn private_temp_dir(id: Id) -> Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let dir = env::temp_dir().join(format!("tdlist-note-{}-{}-{}", id, process::id(), nanos));
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder
+        .create(&dir)
+        .with_context(|| format!("Could not create {}", dir.display()))?;
+    Ok(dir)
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let Args { id } = args;
+    let task = app.get_task_mut(id)?;
+
+    let dir = private_temp_dir(id)?;
+    let path = dir.join("note.md");
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?
+        .write_all(task.description.as_deref().unwrap_or_default().as_bytes())?;
+
+    let editor = editor();
+    let (program, editor_args) = editor
+        .split_first()
+        .ok_or_else(|| anyhow!("No editor configured"))?;
+    let status = process::Command::new(program)
+        .args(editor_args)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Could not start editor {}", program));
+    let notes = fs::read_to_string(&path);
+    fs::remove_dir_all(&dir)?;
+
+    let status = status?;
+    if !status.success() {
+        return Err(anyhow!(
+            "Editor exited with {}, notes left unchanged",
+            status
+        ));
+    }
+
+    let notes = notes?.trim_end().to_string();
+    if notes.is_empty() {
+        task.description = None;
+    } else {
+        task.set_description(notes);
+    }
+    app.save_state();
+    Ok(())
+}
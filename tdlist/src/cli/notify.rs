@@ -0,0 +1,53 @@
+use crate::app::App;
+use crate::notifier::{self, Notifier};
+
+use anyhow::Result;
+use clap::Parser;
+use std::{thread, time};
+
+#[derive(Parser)]
+pub struct Args {
+    /// Check for due reminders once and exit
+    #[arg(long)]
+    once: bool,
+    /// Seconds to wait between checks
+    #[arg(long, default_value_t = 60)]
+    interval: u64,
+}
+
+#[derive(Parser)]
+pub struct DaemonArgs {
+    /// Seconds to wait between checks
+    #[arg(long, default_value_t = 60)]
+    interval: u64,
+}
+
+fn check(app: &mut App, notifier: &dyn Notifier) -> Result<()> {
+    app.reload();
+    let now = chrono::Utc::now();
+    notifier::fire_reminders(app, notifier, now)?;
+    Ok(())
+}
+
+fn watch(mut app: App, interval: u64) -> Result<()> {
+    let notifier = notifier::from_settings(&app.settings.notifier);
+    loop {
+        if let Err(e) = check(&mut app, notifier.as_ref()) {
+            eprintln!("Failed to send reminders: {}", e);
+        }
+        thread::sleep(time::Duration::from_secs(interval));
+    }
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let Args { once, interval } = args;
+    if once {
+        let notifier = notifier::from_settings(&app.settings.notifier);
+        return check(&mut app, notifier.as_ref());
+    }
+    watch(app, interval)
+}
+
+pub fn run_daemon(app: App, args: DaemonArgs) -> Result<()> {
+    watch(app, args.interval)
+}
@@ -0,0 +1,66 @@
+use crate::app::{App, Id};
+
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use std::process;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the task to open
+    id: Id,
+    /// Open every link of the task
+    #[arg(long, conflicts_with = "index")]
+    all: bool,
+    /// Which link to open when the task has several, starting from 1
+    #[arg(long)]
+    index: Option<usize>,
+}
+
+/// Runs the configured opener, which may include arguments such as
+/// `firefox --new-tab`
+fn open(opener: &str, url: &str) -> Result<()> {
+    let mut parts = opener.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow!("No opener configured"))?;
+    let status = process::Command::new(program)
+        .args(parts)
+        .arg(url)
+        .status()
+        .with_context(|| format!("Could not run {}", program))?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let Args { id, all, index } = args;
+    let task = app
+        .get_task(id)
+        .ok_or_else(|| anyhow!("Task {} not found", id))?;
+    let urls = task.link_targets();
+
+    let selected: Vec<&String> = match (urls.len(), index) {
+        (0, _) => return Err(anyhow!("Task {} has no links", id)),
+        (_, Some(i)) => vec![urls
+            .get(i.wrapping_sub(1))
+            .ok_or_else(|| anyhow!("Task {} has no link {}", id, i))?],
+        (1, None) => vec![&urls[0]],
+        (_, None) if all => urls.iter().collect(),
+        (n, None) => {
+            for (i, url) in urls.iter().enumerate() {
+                println!("{}: {}", i + 1, url);
+            }
+            return Err(anyhow!(
+                "Task {} has {} links, pick one with --index or open them with --all",
+                id,
+                n
+            ));
+        }
+    };
+    for url in selected {
+        open(&app.settings.opener, url)?;
+    }
+    Ok(())
+}
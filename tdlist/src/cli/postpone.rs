@@ -0,0 +1,29 @@
+use crate::app::{App, Id};
+use crate::utils;
+
+use anyhow::Result;
+use clap::Parser;
+
+use super::cli_utils;
+use super::formats::Format;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the task to postpone
+    id: Id,
+    /// The new date: today, tomorrow, a weekday, +3d or a full date
+    #[arg(long)]
+    to: String,
+    /// The format to display the task with
+    #[arg(long)]
+    format: Option<Format>,
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let Args { id, to, format } = args;
+    let to = utils::parse_relative_date(&to, &app.settings)?;
+    app.get_task_mut(id)?.postpone(to);
+    app.save_state();
+    cli_utils::print_task(app.get_task(id).unwrap(), format, &app.settings);
+    Ok(())
+}
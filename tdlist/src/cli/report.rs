@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+
+use crate::app::{App, Id};
+use crate::utils;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+
+use super::formats::Format;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: ReportCommand,
+}
+
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// Sums up time logged on tasks, by group
+    Time {
+        /// Only count tasks in this group or the groups nested under it
+        #[arg(long)]
+        group: Option<String>,
+        /// Count time logged from the start of this day
+        #[arg(long)]
+        from: Option<String>,
+        /// Count time logged up to the end of this day
+        #[arg(long)]
+        to: Option<String>,
+        /// The format to print the report with
+        #[arg(short, long)]
+        format: Option<Format>,
+    },
+}
+
+#[derive(Serialize)]
+struct TaskTime {
+    id: Id,
+    name: String,
+    logged_minutes: i64,
+    estimate_minutes: Option<i64>,
+    #[serde(skip)]
+    logged: Duration,
+}
+
+#[derive(Serialize)]
+struct GroupTime {
+    group: Option<String>,
+    logged_minutes: i64,
+    tasks: Vec<TaskTime>,
+    #[serde(skip)]
+    logged: Duration,
+}
+
+#[derive(Serialize)]
+struct TimeReport {
+    logged_minutes: i64,
+    groups: Vec<GroupTime>,
+}
+
+/// Rounds to the nearest minute. Totals are summed from the exact durations
+/// and rounded once so seconds aren't lost per task.
+fn round_minutes(d: Duration) -> i64 {
+    (d.num_seconds() + 30).div_euclid(60)
+}
+
+fn minutes(m: i64) -> String {
+    utils::duration_to_display_str(&Duration::minutes(m))
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let ReportCommand::Time {
+        group,
+        from,
+        to,
+        format,
+    } = args.command;
+    let settings = &app.settings;
+    let from = match from {
+        Some(from) => Some(utils::parse_relative_date(&from, settings)?.start()),
+        None => None,
+    };
+    let to = match to {
+        Some(to) => Some(utils::parse_relative_date(&to, settings)?.deadline()),
+        None => None,
+    };
+
+    // Time logged on archived tasks still has to be billed
+    let now = Utc::now();
+    let archive = app.load_archive();
+    let mut groups: BTreeMap<Option<String>, Vec<TaskTime>> = BTreeMap::new();
+    for task in app.tasks.values().chain(archive.values()) {
+        if group.as_ref().is_some_and(|g| !task.in_group(g)) {
+            continue;
+        }
+        let logged = task.time_logged(from, to, now);
+        if logged <= Duration::zero() {
+            continue;
+        }
+        groups
+            .entry(task.group.clone())
+            .or_default()
+            .push(TaskTime {
+                id: task.id.unwrap_or_default(),
+                name: task.name.clone(),
+                logged_minutes: round_minutes(logged),
+                estimate_minutes: task.estimate_minutes,
+                logged,
+            });
+    }
+    let groups: Vec<GroupTime> = groups
+        .into_iter()
+        .map(|(group, mut tasks)| {
+            tasks.sort_by_key(|t| (-t.logged, t.id));
+            let logged = tasks
+                .iter()
+                .fold(Duration::zero(), |total, t| total + t.logged);
+            GroupTime {
+                group,
+                logged_minutes: round_minutes(logged),
+                tasks,
+                logged,
+            }
+        })
+        .collect();
+    let logged = groups
+        .iter()
+        .fold(Duration::zero(), |total, g| total + g.logged);
+    let report = TimeReport {
+        logged_minutes: round_minutes(logged),
+        groups,
+    };
+
+    match format {
+        Some(Format::Json) => println!(
+            "{}",
+            serde_json::to_string(&report).expect("Failed to serialize report to json")
+        ),
+        Some(Format::JsonPretty) => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Failed to serialize report to json")
+        ),
+        _ => {
+            let longest_name = report
+                .groups
+                .iter()
+                .flat_map(|g| &g.tasks)
+                .map(|t| format!("{} ({})", t.name, t.id).len())
+                .max()
+                .unwrap_or(0);
+            for group in &report.groups {
+                println!(
+                    "{}  {}",
+                    group.group.as_deref().unwrap_or("(none)"),
+                    minutes(group.logged_minutes)
+                );
+                for task in &group.tasks {
+                    let estimate = task
+                        .estimate_minutes
+                        .map(|e| format!(" of {} estimated", minutes(e)))
+                        .unwrap_or_default();
+                    println!(
+                        "  {:width$}  {}{}",
+                        format!("{} ({})", task.name, task.id),
+                        minutes(task.logged_minutes),
+                        estimate,
+                        width = longest_name
+                    );
+                }
+                println!();
+            }
+            println!("Total  {}", minutes(report.logged_minutes));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_to_nearest_minute() {
+        assert_eq!(round_minutes(Duration::seconds(29)), 0);
+        assert_eq!(round_minutes(Duration::seconds(30)), 1);
+        assert_eq!(round_minutes(Duration::seconds(89)), 1);
+        assert_eq!(round_minutes(Duration::seconds(90)), 2);
+    }
+
+    #[test]
+    fn totals_round_summed_durations() {
+        let logged = [Duration::seconds(40); 3]
+            .iter()
+            .fold(Duration::zero(), |total, d| total + *d);
+        assert_eq!(round_minutes(logged), 2);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::app::{App, Id};
+use crate::configuration::Settings;
+use crate::due_date::DueDate;
+use crate::occurrence;
+use crate::reminder;
+use crate::task::Task;
+use crate::utils;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use itertools::Itertools;
+use serde::Serialize;
+use std::io::IsTerminal;
+
+use super::formats::Format;
+use super::markdown;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the task to show
+    id: Id,
+    /// The format to print the task with
+    #[arg(short, long)]
+    format: Option<Format>,
+    /// How many upcoming occurrences of a repeating task to list
+    #[arg(long, default_value_t = 5)]
+    occurrences: usize,
+}
+
+#[derive(Serialize)]
+struct TaskDetails<'a> {
+    #[serde(flatten)]
+    task: &'a Task,
+    next_occurrences: Vec<DueDate>,
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let Args {
+        id,
+        format,
+        occurrences,
+    } = args;
+    let task = app
+        .get_task(id)
+        .ok_or_else(|| anyhow!("Task {} not found", id))?;
+    let next_occurrences = if task.complete {
+        vec![]
+    } else {
+        occurrence::occurrences(task)
+            .skip(1)
+            .take(occurrences)
+            .collect()
+    };
+    let details = TaskDetails {
+        task,
+        next_occurrences,
+    };
+
+    match format {
+        Some(Format::Json) => println!(
+            "{}",
+            serde_json::to_string(&details).expect("Failed to serialize task to json")
+        ),
+        Some(Format::JsonPretty) => println!(
+            "{}",
+            serde_json::to_string_pretty(&details).expect("Failed to serialize task to json")
+        ),
+        _ => print_details(&details, &app.settings),
+    }
+    Ok(())
+}
+
+fn print_details(details: &TaskDetails, settings: &Settings) {
+    let task = details.task;
+    let field = |label: &str, value: String| println!("{:12}{}", format!("{}:", label), value);
+    let timestamp = |dt: &Option<DateTime<Utc>>| {
+        dt.map(|dt| {
+            utils::to_local(&dt)
+                .format(&settings.date_formats.display_datetime_format)
+                .to_string()
+        })
+        .unwrap_or_default()
+    };
+
+    let icon = settings.icons.get_complete_icon(task.complete);
+    println!("{} {} ({})", icon, task.name, task.id.unwrap());
+    println!();
+    field("Due", utils::date_to_display_str(&task.date, settings));
+    field(
+        "Timezone",
+        task.timezone.map(|tz| tz.to_string()).unwrap_or_default(),
+    );
+    field(
+        "Scheduled",
+        task.scheduled
+            .map(|d| utils::date_to_display_str(&d, settings))
+            .unwrap_or_default(),
+    );
+    let repeats = match task.repeats.to_rrule() {
+        Some(rrule) => format!("{} (RRULE:{})", task.repeats, rrule),
+        None => task.repeats.to_string(),
+    };
+    field("Repeats", repeats);
+    field("Group", task.group.clone().unwrap_or_default());
+    field(
+        "Priority",
+        task.priority.map(|p| p.to_string()).unwrap_or_default(),
+    );
+    field("Tags", task.tags.join(", "));
+    field("Links", task.links.iter().join(", "));
+    field(
+        "Reminders",
+        reminder::reminders_to_display_str(&task.reminders),
+    );
+    field(
+        "Estimate",
+        task.estimate()
+            .map(|d| utils::duration_to_display_str(&d))
+            .unwrap_or_default(),
+    );
+    let logged = utils::duration_to_display_str(&task.time_logged(None, None, Utc::now()));
+    let logged = match task.running_entry() {
+        Some(entry) => format!(
+            "{} (running since {})",
+            logged,
+            timestamp(&Some(entry.start))
+        ),
+        None => logged,
+    };
+    field("Logged", logged);
+    field("Created", timestamp(&task.created_at));
+    field("Modified", timestamp(&task.modified_at));
+    field("Completed", timestamp(&task.completed_at));
+    field(
+        "Next",
+        details
+            .next_occurrences
+            .iter()
+            .map(|d| utils::date_to_display_str(d, settings))
+            .join(", "),
+    );
+
+    if !task.history.is_empty() {
+        println!();
+        println!("History:");
+        for entry in &task.history {
+            let due = utils::date_to_display_str(&entry.due, settings);
+            println!(
+                "  {:9}  {}  (due {})",
+                entry.kind,
+                timestamp(&Some(entry.at)),
+                due
+            );
+        }
+    }
+
+    if let Some(description) = &task.description {
+        println!();
+        let styled = std::io::stdout().is_terminal();
+        println!("{}", markdown::render(description, settings, styled));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::app::{App, Id};
+
+use anyhow::Result;
+use chrono::Utc;
+use clap::Parser;
+
+use super::cli_utils;
+use super::formats::Format;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the repeating task to skip
+    id: Id,
+    /// The format to display the task with
+    #[arg(long)]
+    format: Option<Format>,
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let Args { id, format } = args;
+    app.get_task_mut(id)?.skip(Utc::now())?;
+    app.save_state();
+    cli_utils::print_task(app.get_task(id).unwrap(), format, &app.settings);
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::app::{App, Id};
+use crate::utils;
+
+use anyhow::Result;
+use clap::Parser;
+
+use super::cli_utils;
+use super::formats::Format;
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the task to snooze
+    id: Id,
+    /// How long to push the task out by, e.g. 2h, 2d or 1w
+    duration: String,
+    /// The format to display the task with
+    #[arg(long)]
+    format: Option<Format>,
+}
+
+pub fn run(mut app: App, args: Args) -> Result<()> {
+    let Args {
+        id,
+        duration,
+        format,
+    } = args;
+    let duration = utils::parse_duration(&duration)?;
+    app.get_task_mut(id)?.snooze(duration)?;
+    app.save_state();
+    cli_utils::print_task(app.get_task(id).unwrap(), format, &app.settings);
+    Ok(())
+}
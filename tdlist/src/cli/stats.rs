@@ -0,0 +1,112 @@
+use crate::app::App;
+use crate::stats::{self, DayCount};
+use crate::utils;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::Parser;
+
+use super::formats::Format;
+
+/// The width of the longest bar in a chart
+const BAR_WIDTH: usize = 40;
+
+#[derive(Parser)]
+pub struct Args {
+    /// How many days back to report on, including today
+    #[arg(long, default_value_t = 14)]
+    days: u32,
+    /// The format to print the statistics with
+    #[arg(short, long)]
+    format: Option<Format>,
+}
+
+fn print_chart(counts: &[DayCount], label: impl Fn(&DayCount) -> String) {
+    let max = counts.iter().map(|c| c.count).max().unwrap_or(0).max(1);
+    for count in counts {
+        let bar = "█".repeat(count.count * BAR_WIDTH / max);
+        println!("  {}  {:>3} {}", label(count), count.count, bar);
+    }
+}
+
+pub fn run(app: App, args: Args) -> Result<()> {
+    let Args { days, format } = args;
+    // Archived tasks were completed too, so they count towards the history
+    let mut tasks = app.load_archive();
+    tasks.extend(app.tasks.clone());
+    let stats = stats::compute(&tasks, Utc::now(), days);
+
+    match format {
+        Some(Format::Json) => println!(
+            "{}",
+            serde_json::to_string(&stats).expect("Failed to serialize stats to json")
+        ),
+        Some(Format::JsonPretty) => println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).expect("Failed to serialize stats to json")
+        ),
+        _ => {
+            let date_format = &app.settings.date_formats.display_date_format;
+            println!(
+                "From {} to {}",
+                stats.from.format(date_format),
+                stats.to.format(date_format)
+            );
+            println!();
+            println!("Completed:        {}", stats.completed);
+            println!("Open:             {}", stats.open);
+            println!("Overdue:          {}", stats.overdue);
+            let lateness = stats
+                .average_lateness_minutes
+                .map(|m| utils::duration_to_display_str(&Duration::minutes(m)))
+                .unwrap_or("-".to_string());
+            println!(
+                "Average lateness: {} ({} late)",
+                lateness, stats.late_completions
+            );
+
+            println!();
+            println!("Completed per day");
+            print_chart(&stats.completed_per_day, |c| {
+                c.date.format("%a %m-%d").to_string()
+            });
+
+            println!();
+            println!("Completed per week");
+            print_chart(&stats.completed_per_week, |c| {
+                format!("{:9}", c.date.format("%G-W%V"))
+            });
+
+            println!();
+            println!("Groups");
+            let longest_group = stats
+                .groups
+                .iter()
+                .map(|g| g.group.as_deref().unwrap_or("(none)").len())
+                .max()
+                .unwrap_or(0);
+            for group in &stats.groups {
+                println!(
+                    "  {:width$}  {} open, {} overdue, {} completed",
+                    group.group.as_deref().unwrap_or("(none)"),
+                    group.open,
+                    group.overdue,
+                    group.completed,
+                    width = longest_group
+                );
+            }
+
+            if !stats.streaks.is_empty() {
+                println!();
+                println!("Streaks");
+                for streak in &stats.streaks {
+                    println!(
+                        "  {} ({}): {} in a row, best {}",
+                        streak.name, streak.id, streak.current, streak.longest
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
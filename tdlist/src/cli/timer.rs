@@ -0,0 +1,76 @@
+use crate::app::{App, Id};
+use crate::utils;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use clap::Parser;
+
+#[derive(Parser)]
+pub struct StartArgs {
+    /// The id of the task to work on
+    id: Id,
+}
+
+#[derive(Parser)]
+pub struct StopArgs {
+    /// The id of the task to stop, defaults to whichever is running
+    id: Option<Id>,
+}
+
+/// Tasks with a timer running, oldest id first
+fn running(app: &App) -> Vec<Id> {
+    let mut ids: Vec<Id> = app
+        .tasks
+        .iter()
+        .filter(|(_, t)| t.running_entry().is_some())
+        .map(|(&id, _)| id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn stop(app: &mut App, id: Id) -> Result<()> {
+    let now = Utc::now();
+    let task = app.get_task_mut(id)?;
+    let spent = task
+        .stop_timer(now)
+        .ok_or_else(|| anyhow!("{} isn't being timed", task.name))?;
+    println!(
+        "Stopped {} ({}) after {}",
+        task.name,
+        id,
+        utils::duration_to_display_str(&spent)
+    );
+    Ok(())
+}
+
+/// Only one task is timed at a time, so starting one stops the others
+pub fn run_start(mut app: App, args: StartArgs) -> Result<()> {
+    let StartArgs { id } = args;
+    if app.get_task(id).is_none() {
+        return Err(anyhow!("Task {} not found", id));
+    }
+    for other in running(&app).into_iter().filter(|&other| other != id) {
+        stop(&mut app, other)?;
+    }
+    let task = app.get_task_mut(id)?;
+    task.start_timer(Utc::now())?;
+    println!("Started {} ({})", task.name, id);
+    app.save_state();
+    Ok(())
+}
+
+pub fn run_stop(mut app: App, args: StopArgs) -> Result<()> {
+    let ids = match args.id {
+        Some(id) => vec![id],
+        None => running(&app),
+    };
+    if ids.is_empty() {
+        return Err(anyhow!("No task is being timed"));
+    }
+    for id in ids {
+        stop(&mut app, id)?;
+    }
+    app.save_state();
+    Ok(())
+}
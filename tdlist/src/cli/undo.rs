@@ -0,0 +1,19 @@
+use crate::app::App;
+
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser)]
+pub struct Args {}
+
+pub fn run(mut app: App, _args: Args) -> Result<()> {
+    let (restored, skipped) = app.undo()?;
+    println!("Restored {} tasks", restored);
+    if skipped > 0 {
+        println!(
+            "Skipped {} tasks changed or archived since, undo those by hand",
+            skipped
+        );
+    }
+    Ok(())
+}
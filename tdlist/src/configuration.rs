@@ -107,6 +107,34 @@ impl Color {
         }
     }
 
+    pub fn to_crossterm_color(&self) -> crossterm::style::Color {
+        match self {
+            Color::Reset => crossterm::style::Color::Reset,
+            Color::Black => crossterm::style::Color::Black,
+            Color::Red => crossterm::style::Color::DarkRed,
+            Color::Green => crossterm::style::Color::DarkGreen,
+            Color::Yellow => crossterm::style::Color::DarkYellow,
+            Color::Blue => crossterm::style::Color::DarkBlue,
+            Color::Magenta => crossterm::style::Color::DarkMagenta,
+            Color::Cyan => crossterm::style::Color::DarkCyan,
+            Color::Gray => crossterm::style::Color::Grey,
+            Color::DarkGray => crossterm::style::Color::DarkGrey,
+            Color::LightRed => crossterm::style::Color::Red,
+            Color::LightGreen => crossterm::style::Color::Green,
+            Color::LightYellow => crossterm::style::Color::Yellow,
+            Color::LightBlue => crossterm::style::Color::Blue,
+            Color::LightMagenta => crossterm::style::Color::Magenta,
+            Color::LightCyan => crossterm::style::Color::Cyan,
+            Color::White => crossterm::style::Color::White,
+            Color::Rgb(r, g, b) => crossterm::style::Color::Rgb {
+                r: *r,
+                g: *g,
+                b: *b,
+            },
+            Color::Indexed(i) => crossterm::style::Color::AnsiValue(*i),
+        }
+    }
+
     pub fn from_tui_color(color: tui::style::Color) -> Self {
         match color {
             tui::style::Color::Reset => Color::Reset,
@@ -164,6 +192,16 @@ pub struct Colors {
         deserialize_with = "deserialize_color"
     )]
     pub accent_color: tui::style::Color,
+    #[serde(
+        serialize_with = "serialize_color",
+        deserialize_with = "deserialize_color",
+        default = "default_overdue_color"
+    )]
+    pub overdue_color: tui::style::Color,
+}
+
+fn default_overdue_color() -> tui::style::Color {
+    tui::style::Color::LightRed
 }
 
 impl Colors {
@@ -172,6 +210,7 @@ impl Colors {
             primary_color: tui::style::Color::LightGreen,
             secondary_color: tui::style::Color::LightYellow,
             accent_color: tui::style::Color::LightBlue,
+            overdue_color: default_overdue_color(),
         }
     }
 }
@@ -324,6 +363,23 @@ impl Default for KeyBindings {
     }
 }
 
+/// How reminders are delivered by `tdlist notify` and `tdlist daemon`
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub enum NotifierSettings {
+    /// Print reminders to standard output
+    #[default]
+    Stdout,
+    /// Run a desktop notification command with the title and body as
+    /// arguments, e.g. `notify-send`
+    Desktop { command: String },
+    /// Run a shell command with the task exposed in `TDLIST_*` variables
+    Hook { command: String },
+}
+
+fn default_opener() -> String {
+    String::from("xdg-open")
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Settings {
     pub date_formats: DateFormats,
@@ -332,6 +388,17 @@ pub struct Settings {
     pub icons: Icons,
     pub colors: Colors,
     pub keybindings: KeyBindings,
+    #[serde(default)]
+    pub notifier: NotifierSettings,
+    /// The command links are opened with
+    #[serde(default = "default_opener")]
+    pub opener: String,
+    /// Groups hidden from `ls` unless asked for by name
+    #[serde(default)]
+    pub archived_groups: Vec<String>,
+    /// Archive completed tasks left unchanged for this long, e.g. "30d"
+    #[serde(default)]
+    pub auto_archive: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -342,6 +409,31 @@ pub struct SettingsBuilder {
     pub icons: Icons,
     pub colors: Colors,
     pub keybindings: KeyBindings,
+    #[serde(default)]
+    pub notifier: NotifierSettings,
+    /// The command links are opened with
+    #[serde(default = "default_opener")]
+    pub opener: String,
+    /// Groups hidden from `ls` unless asked for by name
+    #[serde(default)]
+    pub archived_groups: Vec<String>,
+    /// Archive completed tasks left unchanged for this long, e.g. "30d"
+    #[serde(default)]
+    pub auto_archive: Option<String>,
+}
+
+impl Settings {
+    pub fn save_to_file(&self) -> Result<()> {
+        write_settings(self)
+    }
+}
+
+/// Writes settings.json in the config directory
+fn write_settings(settings: &impl Serialize) -> Result<()> {
+    let path = SettingsBuilder::default_path()?.join("settings.json");
+    let settings_json = serde_json::to_string_pretty(settings)?;
+    fs::write(&path, settings_json)?;
+    Ok(())
 }
 
 impl SettingsBuilder {
@@ -359,23 +451,42 @@ impl SettingsBuilder {
     }
 
     pub fn save_to_file(&self) -> Result<()> {
-        let default_path = Self::default_path()?;
-        let path = default_path.join("settings.json");
-        let settings_json = serde_json::to_string_pretty(&self)?;
-        fs::write(&path, settings_json)?;
-        Ok(())
+        write_settings(self)
     }
 
     pub fn get_default_db_file() -> Result<PathBuf> {
+        Self::get_task_file("tasks.json")
+    }
+
+    pub fn get_default_archive_file() -> Result<PathBuf> {
+        Self::get_task_file("archive.json")
+    }
+
+    /// A task database in the config directory, created empty if needed
+    fn get_task_file(name: &str) -> Result<PathBuf> {
         let default_path = Self::default_path()?;
-        let path = default_path.join("tasks.json");
+        let path = default_path.join(name);
         if !path.exists() {
-            let mut file = OpenOptions::new().write(true).create(true).open(&path)?;
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)?;
             writeln!(file, "{{}}")?;
         }
         Ok(path)
     }
 
+    pub fn get_undo_file() -> Result<PathBuf> {
+        let default_path = Self::default_path()?;
+        Ok(default_path.join("undo.json"))
+    }
+
+    pub fn get_attachments_dir() -> Result<PathBuf> {
+        let default_path = Self::default_path()?;
+        Ok(default_path.join("attachments"))
+    }
+
     pub fn get_settings_path() -> Result<PathBuf> {
         let default_path = Self::default_path()?;
         let path = default_path.join("settings.json");
@@ -395,10 +506,21 @@ impl Default for SettingsBuilder {
             date_formats: DateFormats::new(),
             colors: Colors::default(),
             keybindings: KeyBindings::default(),
+            notifier: NotifierSettings::default(),
+            opener: default_opener(),
+            archived_groups: Vec::new(),
+            auto_archive: None,
         }
     }
 }
 
+/// The settings a new config directory starts with, without touching disk
+#[cfg(test)]
+pub fn default_settings() -> Settings {
+    let defaults = serde_json::to_value(SettingsBuilder::default()).unwrap();
+    serde_json::from_value(defaults).unwrap()
+}
+
 pub fn get_configuration() -> Settings {
     let settings_path = SettingsBuilder::get_settings_path().expect("Settings file should exist.");
     let file = OpenOptions::new()
@@ -411,3 +533,15 @@ pub fn get_configuration() -> Settings {
 pub fn get_db_file() -> PathBuf {
     SettingsBuilder::get_default_db_file().expect("Could not find default task file")
 }
+
+pub fn get_archive_file() -> PathBuf {
+    SettingsBuilder::get_default_archive_file().expect("Could not find archive file")
+}
+
+pub fn get_undo_file() -> PathBuf {
+    SettingsBuilder::get_undo_file().expect("Could not find undo file")
+}
+
+pub fn get_attachments_dir() -> PathBuf {
+    SettingsBuilder::get_attachments_dir().expect("Could not find attachments directory")
+}
@@ -5,7 +5,7 @@ use std::{
     str::FromStr,
 };
 
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DayOfWeek {
     Monday,
     Tuesday,
@@ -40,6 +40,54 @@ impl DayOfWeek {
             chrono::Weekday::Sun => Self::Sunday,
         }
     }
+
+    pub fn to_chrono(&self) -> chrono::Weekday {
+        match self {
+            DayOfWeek::Monday => chrono::Weekday::Mon,
+            DayOfWeek::Tuesday => chrono::Weekday::Tue,
+            DayOfWeek::Wednesday => chrono::Weekday::Wed,
+            DayOfWeek::Thursday => chrono::Weekday::Thu,
+            DayOfWeek::Friday => chrono::Weekday::Fri,
+            DayOfWeek::Saturday => chrono::Weekday::Sat,
+            DayOfWeek::Sunday => chrono::Weekday::Sun,
+        }
+    }
+
+    /// The two letter code used by iCalendar's BYDAY
+    pub fn to_rrule(&self) -> &'static str {
+        match self {
+            DayOfWeek::Monday => "MO",
+            DayOfWeek::Tuesday => "TU",
+            DayOfWeek::Wednesday => "WE",
+            DayOfWeek::Thursday => "TH",
+            DayOfWeek::Friday => "FR",
+            DayOfWeek::Saturday => "SA",
+            DayOfWeek::Sunday => "SU",
+        }
+    }
+
+    pub fn from_rrule(s: &str) -> Result<DayOfWeek> {
+        match s.to_uppercase().as_str() {
+            "MO" => Ok(DayOfWeek::Monday),
+            "TU" => Ok(DayOfWeek::Tuesday),
+            "WE" => Ok(DayOfWeek::Wednesday),
+            "TH" => Ok(DayOfWeek::Thursday),
+            "FR" => Ok(DayOfWeek::Friday),
+            "SA" => Ok(DayOfWeek::Saturday),
+            "SU" => Ok(DayOfWeek::Sunday),
+            _ => Err(anyhow::anyhow!("Invalid day of the week")),
+        }
+    }
+
+    pub fn weekdays() -> Vec<DayOfWeek> {
+        vec![
+            DayOfWeek::Monday,
+            DayOfWeek::Tuesday,
+            DayOfWeek::Wednesday,
+            DayOfWeek::Thursday,
+            DayOfWeek::Friday,
+        ]
+    }
 }
 
 impl FromStr for DayOfWeek {
@@ -47,13 +95,13 @@ impl FromStr for DayOfWeek {
 
     fn from_str(s: &str) -> Result<DayOfWeek, Self::Err> {
         match s.to_lowercase().as_str() {
-            "mon" => Ok(DayOfWeek::Monday),
-            "tue" => Ok(DayOfWeek::Tuesday),
-            "wed" => Ok(DayOfWeek::Wednesday),
-            "thu" => Ok(DayOfWeek::Thursday),
-            "fri" => Ok(DayOfWeek::Friday),
-            "sat" => Ok(DayOfWeek::Saturday),
-            "sun" => Ok(DayOfWeek::Sunday),
+            "mon" | "monday" => Ok(DayOfWeek::Monday),
+            "tue" | "tuesday" => Ok(DayOfWeek::Tuesday),
+            "wed" | "wednesday" => Ok(DayOfWeek::Wednesday),
+            "thu" | "thursday" => Ok(DayOfWeek::Thursday),
+            "fri" | "friday" => Ok(DayOfWeek::Friday),
+            "sat" | "saturday" => Ok(DayOfWeek::Saturday),
+            "sun" | "sunday" => Ok(DayOfWeek::Sunday),
             _ => Err(anyhow::anyhow!("Invalid day of the week")),
         }
     }
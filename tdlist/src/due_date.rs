@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::utils;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DueDate {
+    /// Due at some point during the day, with no particular time
+    Date(NaiveDate),
+    /// Due at an exact instant
+    DateTime(DateTime<Utc>),
+}
+
+impl DueDate {
+    pub fn has_time(&self) -> bool {
+        matches!(self, DueDate::DateTime(_))
+    }
+
+    /// The calendar day the task is due on, in the viewer's timezone
+    pub fn local_date(&self) -> NaiveDate {
+        match self {
+            DueDate::Date(date) => *date,
+            DueDate::DateTime(dt) => utils::to_local(dt).date_naive(),
+        }
+    }
+
+    /// The calendar day the task is due on in `tz`, or in the viewer's
+    /// timezone when the task has none
+    pub fn date_in(&self, tz: Option<&Tz>) -> NaiveDate {
+        match (self, tz) {
+            (DueDate::DateTime(dt), Some(tz)) => dt.with_timezone(tz).date_naive(),
+            _ => self.local_date(),
+        }
+    }
+
+    /// Moves the due date to another day, keeping its wall clock time in `tz`
+    /// (or the viewer's timezone) so it doesn't drift across DST changes
+    pub fn with_date(&self, date: NaiveDate, tz: Option<&Tz>) -> DueDate {
+        match (self, tz) {
+            (DueDate::Date(_), _) => DueDate::Date(date),
+            (DueDate::DateTime(dt), Some(tz)) => {
+                let time = dt.with_timezone(tz).time();
+                DueDate::DateTime(utils::resolve_local(tz, &date.and_time(time)))
+            }
+            (DueDate::DateTime(dt), None) => {
+                let time = utils::to_local(dt).time();
+                DueDate::DateTime(utils::resolve_local(&Local, &date.and_time(time)))
+            }
+        }
+    }
+
+    /// Moves the due date by `by`. Whole days keep the wall clock time in
+    /// `tz`; date-only due dates can only move by whole days.
+    pub fn shifted(&self, by: Duration, tz: Option<&Tz>) -> Result<DueDate> {
+        let out_of_range = || {
+            anyhow!(
+                "Moving by {} is out of range",
+                utils::duration_to_display_str(&by)
+            )
+        };
+        if by.num_seconds() % 86400 == 0 {
+            let day = self
+                .date_in(tz)
+                .checked_add_signed(Duration::days(by.num_days()))
+                .ok_or_else(out_of_range)?;
+            return Ok(self.with_date(day, tz));
+        }
+        match self {
+            DueDate::Date(_) => Err(anyhow!("Tasks without a time can only move by whole days")),
+            DueDate::DateTime(dt) => Ok(DueDate::DateTime(
+                dt.checked_add_signed(by).ok_or_else(out_of_range)?,
+            )),
+        }
+    }
+
+    /// The first instant the task is due. Date-only tasks start at the
+    /// beginning of their day in the viewer's timezone.
+    pub fn start(&self) -> DateTime<Utc> {
+        match self {
+            DueDate::Date(date) => utils::resolve_local(&Local, &date.and_time(NaiveTime::MIN)),
+            DueDate::DateTime(dt) => *dt,
+        }
+    }
+
+    /// The last instant the task can be done without being late. Date-only
+    /// tasks are due by the end of their day in the viewer's timezone.
+    pub fn deadline(&self) -> DateTime<Utc> {
+        match self {
+            DueDate::Date(date) => {
+                let end_of_day = date.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+                utils::resolve_local(&Local, &end_of_day)
+            }
+            DueDate::DateTime(dt) => *dt,
+        }
+    }
+
+    /// Whether the date has been reached, counting all of today for date-only
+    pub fn has_arrived(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            DueDate::Date(date) => *date <= utils::to_local(&now).date_naive(),
+            DueDate::DateTime(dt) => *dt <= now,
+        }
+    }
+
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            DueDate::Date(date) => *date < utils::to_local(&now).date_naive(),
+            DueDate::DateTime(dt) => *dt < now,
+        }
+    }
+}
+
+impl Ord for DueDate {
+    /// Orders by deadline, so date-only tasks come after the timed tasks of
+    /// the same day.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.deadline()
+            .cmp(&other.deadline())
+            .then_with(|| self.has_time().cmp(&other.has_time()).reverse())
+    }
+}
+
+impl PartialOrd for DueDate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,110 @@
+//! Groups are paths such as `work/backend/api`, where each segment nests the
+//! group under the one before it.
+
+use std::collections::BTreeSet;
+
+pub const SEPARATOR: char = '/';
+
+/// Trims and lower-cases every segment and drops empty ones, so "Work /
+/// Backend/" is stored as "work/backend". A blank group is no group.
+pub fn normalize(group: &str) -> Option<String> {
+    let segments: Vec<String> = group
+        .split(SEPARATOR)
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    (!segments.is_empty()).then(|| segments.join(&SEPARATOR.to_string()))
+}
+
+/// Whether `group` is `ancestor` itself or nested somewhere under it
+pub fn is_within(group: &str, ancestor: &str) -> bool {
+    group == ancestor
+        || group
+            .strip_prefix(ancestor)
+            .is_some_and(|rest| rest.starts_with(SEPARATOR))
+}
+
+/// Moves `group` from under `from` to the same place under `to`
+pub fn reparent(group: &str, from: &str, to: &str) -> Option<String> {
+    is_within(group, from).then(|| format!("{}{}", to, &group[from.len()..]))
+}
+
+pub fn parent(group: &str) -> Option<&str> {
+    group.rsplit_once(SEPARATOR).map(|(parent, _)| parent)
+}
+
+/// The last segment of the path
+pub fn name(group: &str) -> &str {
+    group.rsplit(SEPARATOR).next().unwrap_or(group)
+}
+
+pub fn depth(group: &str) -> usize {
+    group.matches(SEPARATOR).count()
+}
+
+/// The groups along with all of their ancestors, parents first and siblings
+/// in alphabetical order
+pub fn hierarchy<'a>(groups: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut all = BTreeSet::new();
+    for group in groups {
+        let mut group = Some(group);
+        while let Some(g) = group {
+            all.insert(g.to_string());
+            group = parent(g);
+        }
+    }
+    let mut all: Vec<String> = all.into_iter().collect();
+    // Comparing whole segments keeps children right after their parent
+    all.sort_by(|a, b| a.split(SEPARATOR).cmp(b.split(SEPARATOR)));
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_segments() {
+        assert_eq!(normalize(" Work / Backend/ "), Some("work/backend".into()));
+        assert_eq!(normalize("//"), None);
+        assert_eq!(normalize(""), None);
+    }
+
+    #[test]
+    fn nesting_goes_by_whole_segments() {
+        assert!(is_within("work", "work"));
+        assert!(is_within("work/backend/api", "work"));
+        assert!(!is_within("workshop", "work"));
+        assert!(!is_within("work", "work/backend"));
+    }
+
+    #[test]
+    fn reparents_nested_groups() {
+        assert_eq!(reparent("work", "work", "job"), Some("job".into()));
+        assert_eq!(
+            reparent("work/backend/api", "work", "job"),
+            Some("job/backend/api".into())
+        );
+        assert_eq!(
+            reparent("work/backend", "work/backend", "work/platform/backend"),
+            Some("work/platform/backend".into())
+        );
+        assert_eq!(reparent("workshop", "work", "job"), None);
+        assert_eq!(reparent("home", "work", "job"), None);
+    }
+
+    #[test]
+    fn hierarchy_includes_parents_in_tree_order() {
+        let groups = hierarchy(["work/backend/api", "home", "work-notes", "work"]);
+        assert_eq!(
+            groups,
+            [
+                "home",
+                "work",
+                "work/backend",
+                "work/backend/api",
+                "work-notes"
+            ]
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use crate::due_date::DueDate;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryKind {
+    Completed,
+    Skipped,
+}
+
+/// Something that happened to one occurrence of a task
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub kind: HistoryKind,
+    /// When it happened
+    pub at: DateTime<Utc>,
+    /// The due date of the occurrence it happened to
+    pub due: DueDate,
+}
+
+impl Display for HistoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryKind::Completed => f.pad("Completed"),
+            HistoryKind::Skipped => f.pad("Skipped"),
+        }
+    }
+}
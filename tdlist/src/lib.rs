@@ -3,7 +3,18 @@ pub mod cli;
 
 pub mod configuration;
 pub mod day_of_week;
+pub mod due_date;
+pub mod group;
+pub mod history;
+pub mod link;
+pub mod notifier;
+pub mod occurrence;
+pub mod priority;
+pub mod quick_add;
+pub mod reminder;
 pub mod repeat;
+pub mod stats;
 pub mod task;
 pub mod task_form;
+pub mod time_entry;
 pub mod utils;
@@ -0,0 +1,137 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum LinkTarget {
+    Url(String),
+    /// A local file, either referenced where it is or copied into the data
+    /// directory
+    File(PathBuf),
+}
+
+/// A link or file attached to a task, like a ticket, PR or design doc
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub label: Option<String>,
+    pub target: LinkTarget,
+}
+
+impl Link {
+    pub fn url(url: String) -> Self {
+        Link {
+            label: None,
+            target: LinkTarget::Url(url),
+        }
+    }
+
+    /// Parses `label=url` or a bare `url`
+    pub fn parse(s: &str) -> Result<Link> {
+        let s = s.trim();
+        let (label, url) = match s.split_once('=') {
+            // Query strings contain '=' too, so only split before a scheme
+            Some((label, url)) if !label.contains("://") => (Some(label.trim()), url.trim()),
+            _ => (None, s),
+        };
+        if url.is_empty() {
+            return Err(anyhow!("Link url cannot be empty"));
+        }
+        Ok(Link {
+            label: label.filter(|l| !l.is_empty()).map(String::from),
+            target: LinkTarget::Url(url.to_string()),
+        })
+    }
+
+    /// References a local file where it is
+    pub fn file(path: &Path, label: Option<String>) -> Result<Link> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Could not find {}", path.display()))?;
+        Ok(Link {
+            label,
+            target: LinkTarget::File(path),
+        })
+    }
+
+    /// Copies a local file into `dir` and links to the copy. A file already
+    /// there with the same name is kept, and the copy gets a numbered name
+    /// like `notes-2.txt` instead.
+    pub fn copy_file(path: &Path, label: Option<String>, dir: &Path) -> Result<Link> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("{} is not a file", path.display()))?;
+        let mut source =
+            File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+        fs::create_dir_all(dir)?;
+        let (mut copy, copy_path) = create_unique(dir, Path::new(file_name))?;
+        io::copy(&mut source, &mut copy)
+            .with_context(|| format!("Could not copy {}", path.display()))?;
+        Link::file(&copy_path, label)
+    }
+
+    /// Whether this is a file copied into `dir`
+    pub fn is_stored_in(&self, dir: &Path) -> bool {
+        match &self.target {
+            LinkTarget::File(path) => dir
+                .canonicalize()
+                .map(|dir| path.starts_with(dir))
+                .unwrap_or(false),
+            LinkTarget::Url(_) => false,
+        }
+    }
+
+    /// Whether this and `other` point at the same file
+    pub fn same_file(&self, other: &Link) -> bool {
+        matches!(
+            (&self.target, &other.target),
+            (LinkTarget::File(a), LinkTarget::File(b)) if a == b
+        )
+    }
+
+    /// What to hand to the opener: the url or the file path
+    pub fn target_str(&self) -> String {
+        match &self.target {
+            LinkTarget::Url(url) => url.clone(),
+            LinkTarget::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// Creates a file in `dir` named after `file_name` that didn't exist before,
+/// numbering the name until one is free
+fn create_unique(dir: &Path, file_name: &Path) -> Result<(File, PathBuf)> {
+    let stem = file_name
+        .file_stem()
+        .unwrap_or(file_name.as_os_str())
+        .to_string_lossy();
+    let extension = file_name
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    for n in 1.. {
+        let path = match n {
+            1 => dir.join(file_name),
+            n => dir.join(format!("{}-{}{}", stem, n, extension)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Could not create {}", path.display()))
+            }
+        }
+    }
+    unreachable!()
+}
+
+impl Display for Link {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{}={}", label, self.target_str()),
+            None => write!(f, "{}", self.target_str()),
+        }
+    }
+}
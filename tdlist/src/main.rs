@@ -5,6 +5,9 @@ fn main() {
     let settings = get_configuration();
     let app = App::new(settings);
     if std::env::args().len() > 1 {
-        _ = cli::start_cli(app);
+        if let Err(e) = cli::start_cli(app) {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
     }
 }
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::process::Command;
+
+use crate::app::{App, Id};
+use crate::configuration::{NotifierSettings, Settings};
+use crate::reminder::Reminder;
+use crate::task::Task;
+use crate::utils;
+
+/// Reminders older than this are dropped instead of fired, so starting the
+/// daemon after a long break doesn't replay every missed reminder.
+const STALE_AFTER_HOURS: i64 = 24;
+
+pub trait Notifier {
+    fn notify(&self, task: &Task, reminder: &Reminder, settings: &Settings) -> Result<()>;
+}
+
+pub struct StdoutNotifier;
+
+impl Notifier for StdoutNotifier {
+    fn notify(&self, task: &Task, reminder: &Reminder, settings: &Settings) -> Result<()> {
+        let (title, body) = message(task, reminder, settings);
+        println!("{}: {}", title, body);
+        Ok(())
+    }
+}
+
+pub struct DesktopNotifier {
+    pub command: String,
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, task: &Task, reminder: &Reminder, settings: &Settings) -> Result<()> {
+        let (title, body) = message(task, reminder, settings);
+        let status = Command::new(&self.command).arg(title).arg(body).status()?;
+        if !status.success() {
+            return Err(anyhow!("{} exited with {}", self.command, status));
+        }
+        Ok(())
+    }
+}
+
+pub struct HookNotifier {
+    pub command: String,
+}
+
+impl Notifier for HookNotifier {
+    fn notify(&self, task: &Task, reminder: &Reminder, settings: &Settings) -> Result<()> {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("TDLIST_TASK_ID", task.id.unwrap_or_default().to_string())
+            .env("TDLIST_TASK_NAME", &task.name)
+            .env(
+                "TDLIST_TASK_DATE",
+                utils::date_to_display_str(&task.date, settings),
+            )
+            .env(
+                "TDLIST_TASK_GROUP",
+                task.group.as_deref().unwrap_or_default(),
+            )
+            .env("TDLIST_REMINDER", reminder.to_string())
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("Hook exited with {}", status));
+        }
+        Ok(())
+    }
+}
+
+fn message(task: &Task, reminder: &Reminder, settings: &Settings) -> (String, String) {
+    let title = format!("Reminder: {}", task.name);
+    let body = format!(
+        "Due {} ({} before)",
+        utils::date_to_display_str(&task.date, settings),
+        reminder
+    );
+    (title, body)
+}
+
+pub fn from_settings(settings: &NotifierSettings) -> Box<dyn Notifier> {
+    match settings {
+        NotifierSettings::Stdout => Box::new(StdoutNotifier),
+        NotifierSettings::Desktop { command } => Box::new(DesktopNotifier {
+            command: command.clone(),
+        }),
+        NotifierSettings::Hook { command } => Box::new(HookNotifier {
+            command: command.clone(),
+        }),
+    }
+}
+
+/// Reminders of incomplete tasks that are due to fire at `now` and haven't
+/// been delivered yet
+pub fn pending_reminders(app: &App, now: DateTime<Utc>) -> Vec<(Id, Reminder)> {
+    let stale = now - Duration::hours(STALE_AFTER_HOURS);
+    let mut pending: Vec<(Id, Reminder)> = app
+        .tasks
+        .iter()
+        .filter(|(_, t)| !t.complete)
+        .flat_map(|(&id, t)| {
+            t.reminders
+                .iter()
+                .filter(|r| {
+                    let fire_at = r.fire_at(&t.date);
+                    fire_at <= now && fire_at > stale && !t.fired_reminders.contains(&fire_at)
+                })
+                .map(move |r| (id, *r))
+        })
+        .collect();
+    pending.sort_by_key(|(id, _)| *id);
+    pending
+}
+
+/// Fires every pending reminder and records it on its task so it isn't
+/// repeated. Returns how many reminders were delivered.
+pub fn fire_reminders(app: &mut App, notifier: &dyn Notifier, now: DateTime<Utc>) -> Result<usize> {
+    let pending = pending_reminders(app, now);
+    let mut fired = Vec::new();
+    for (id, reminder) in pending {
+        let task = app.tasks.get(&id).unwrap();
+        if let Err(e) = notifier.notify(task, &reminder, &app.settings) {
+            // Keep what was delivered before this one, so it isn't sent again
+            record_fired(app, &fired);
+            return Err(e);
+        }
+        fired.push((id, reminder.fire_at(&task.date)));
+    }
+    record_fired(app, &fired);
+    Ok(fired.len())
+}
+
+/// Notifiers can take a while, so tasks are re-read before saving and only
+/// the fired reminders are written back, keeping changes made meanwhile
+fn record_fired(app: &mut App, fired: &[(Id, DateTime<Utc>)]) {
+    if fired.is_empty() {
+        return;
+    }
+    app.reload();
+    for (id, fire_at) in fired {
+        let Some(task) = app.tasks.get_mut(id) else {
+            continue;
+        };
+        // Forget fire times that no longer match a reminder of the task
+        let current: Vec<DateTime<Utc>> = task
+            .reminders
+            .iter()
+            .map(|r| r.fire_at(&task.date))
+            .collect();
+        task.fired_reminders.retain(|t| current.contains(t));
+        task.fired_reminders.push(*fire_at);
+    }
+    app.save_state();
+}
@@ -0,0 +1,43 @@
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+
+use crate::due_date::DueDate;
+use crate::repeat::Repeat;
+use crate::task::Task;
+
+/// A task's current due date followed by every later one its repeat
+/// produces. Repeats counted from completion are projected as if each
+/// occurrence is done on the day it's due.
+pub struct Occurrences {
+    timezone: Option<Tz>,
+    next: Option<(DueDate, Repeat)>,
+}
+
+impl Iterator for Occurrences {
+    type Item = DueDate;
+
+    fn next(&mut self) -> Option<DueDate> {
+        let (date, repeats) = self.next.take()?;
+        let tz = self.timezone.as_ref();
+        self.next = repeats
+            .next(date.date_in(tz))
+            .map(|(day, repeats)| (date.with_date(day, tz), repeats));
+        Some(date)
+    }
+}
+
+pub fn occurrences(task: &Task) -> Occurrences {
+    Occurrences {
+        timezone: task.timezone,
+        next: Some((task.date, task.repeats.clone())),
+    }
+}
+
+/// Every occurrence of the task due between `from` and `to` inclusive, in
+/// the viewer's timezone
+pub fn occurrences_between(task: &Task, from: NaiveDate, to: NaiveDate) -> Vec<DueDate> {
+    occurrences(task)
+        .skip_while(|d| d.local_date() < from)
+        .take_while(|d| d.local_date() <= to)
+        .collect()
+}
@@ -0,0 +1,365 @@
+//! Picks task fields out of a single line, as in
+//! `Pay rent tomorrow 9am every month #home !high +bills @https://bank`.
+//!
+//! - `#group`, which may be nested like `#work/backend`
+//! - `!low`, `!medium` or `!high`
+//! - `+tag`, any number of times
+//! - `@url`
+//! - a date: today, tomorrow, a weekday, next weekday, +3d or a date in the
+//!   configured input format, optionally followed by a time like 9am, 9:30pm
+//!   or at 14:00
+//! - a repeat starting with `every`, or daily, weekly, monthly or yearly
+//!   right after a date or time or at the end
+//!
+//! Everything else is the name. Words that look like one of these but don't
+//! parse are left in the name.
+
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveTime, TimeZone, Utc};
+
+use crate::configuration::Settings;
+use crate::due_date::DueDate;
+use crate::priority::Priority;
+use crate::repeat::Repeat;
+use crate::utils;
+
+/// The longest run of words tried as a repeat, e.g. "every 2 weeks on
+/// mon,thu until 2025-06-30 after completion"
+const MAX_REPEAT_WORDS: usize = 10;
+
+/// Words dropped from the name when they introduce a date or time
+const DATE_PREPOSITIONS: [&str; 4] = ["on", "at", "by", "due"];
+
+/// Words that make a following 24 hour time like 14:00 a due time rather
+/// than part of the name, as in "chapter 3:16"
+const TIME_PREPOSITIONS: [&str; 2] = ["at", "by"];
+
+/// Repeats that are a single word, and so also read as ordinary words
+const BARE_REPEATS: [&str; 4] = ["daily", "weekly", "monthly", "yearly"];
+
+#[derive(Default, Debug)]
+pub struct QuickAdd {
+    pub name: String,
+    pub date: Option<DueDate>,
+    pub repeats: Option<Repeat>,
+    pub group: Option<String>,
+    pub priority: Option<Priority>,
+    pub tags: Vec<String>,
+    pub url: Option<String>,
+}
+
+impl QuickAdd {
+    pub fn parse(text: &str, settings: &Settings) -> Result<QuickAdd> {
+        QuickAdd::parse_in(text, &Local, settings)
+    }
+
+    /// Like `parse`, with dates and times taken as wall clock time in `tz`
+    pub fn parse_in<Tz: TimeZone>(text: &str, tz: &Tz, settings: &Settings) -> Result<QuickAdd> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut quick = QuickAdd::default();
+        let mut name: Vec<&str> = Vec::new();
+        let mut day: Option<DueDate> = None;
+        let mut time: Option<NaiveTime> = None;
+        // Whether the word before this one was taken as a date or time
+        let mut after_date = false;
+
+        let mut i = 0;
+        while i < words.len() {
+            let word = words[i];
+            let rest = &words[i..];
+            let was_after_date = std::mem::take(&mut after_date);
+
+            if let Some(group) = word.strip_prefix('#').filter(|g| !g.is_empty()) {
+                quick.group = Some(group.to_string());
+            } else if let Some(priority) = word.strip_prefix('!').and_then(|p| p.parse().ok()) {
+                quick.priority = Some(priority);
+            } else if let Some(tag) = word
+                .strip_prefix('+')
+                .filter(|t| t.starts_with(|c: char| c.is_alphabetic()))
+            {
+                quick.tags.push(tag.to_lowercase());
+            } else if let Some(url) = word.strip_prefix('@').filter(|u| looks_like_url(u)) {
+                quick.url = Some(url.to_string());
+            } else if let Some((repeat, used)) = parse_repeat(rest).filter(|(_, used)| {
+                let bare = BARE_REPEATS.contains(&word.to_lowercase().as_str());
+                !bare || was_after_date || only_fields(&rest[*used..], tz, settings)
+            }) {
+                quick.repeats = Some(repeat);
+                i += used;
+                continue;
+            } else if let Some((date, used)) =
+                parse_day(rest, tz, settings).filter(|_| day.is_none())
+            {
+                drop_preposition(&mut name);
+                day = Some(date);
+                after_date = true;
+                i += used;
+                continue;
+            } else if let Some(t) =
+                parse_time(word, follows_time_preposition(&name)).filter(|_| time.is_none())
+            {
+                drop_preposition(&mut name);
+                time = Some(t);
+                after_date = true;
+            } else {
+                name.push(word);
+            }
+            i += 1;
+        }
+
+        quick.name = name.join(" ");
+        quick.date = match (day, time) {
+            (Some(DueDate::Date(day)), Some(time)) => Some(DueDate::DateTime(
+                utils::resolve_local(tz, &day.and_time(time)),
+            )),
+            (None, Some(time)) => {
+                let today = Utc::now().with_timezone(tz).date_naive();
+                Some(DueDate::DateTime(utils::resolve_local(
+                    tz,
+                    &today.and_time(time),
+                )))
+            }
+            (day, _) => day,
+        };
+        if quick.name.is_empty() {
+            return Err(anyhow!("Task name cannot be empty"));
+        }
+        Ok(quick)
+    }
+
+    /// Whether anything besides the name was found
+    pub fn extracted_anything(&self) -> bool {
+        self.date.is_some()
+            || self.repeats.is_some()
+            || self.group.is_some()
+            || self.priority.is_some()
+            || !self.tags.is_empty()
+            || self.url.is_some()
+    }
+}
+
+fn looks_like_url(s: &str) -> bool {
+    s.contains("://") || s.starts_with("www.")
+}
+
+fn follows_time_preposition(name: &[&str]) -> bool {
+    name.last()
+        .is_some_and(|w| TIME_PREPOSITIONS.contains(&w.to_lowercase().as_str()))
+}
+
+/// Whether every word reads as a field rather than part of the name, so a
+/// bare repeat before them ends the name
+fn only_fields<Tz: TimeZone>(words: &[&str], tz: &Tz, settings: &Settings) -> bool {
+    words.iter().enumerate().all(|(i, word)| {
+        word.len() > 1 && word.starts_with(['#', '!', '+', '@'])
+            || parse_time(word, false).is_some()
+            || parse_day(&words[i..i + 1], tz, settings).is_some()
+    })
+}
+
+fn drop_preposition(name: &mut Vec<&str>) {
+    if name
+        .last()
+        .is_some_and(|w| DATE_PREPOSITIONS.contains(&w.to_lowercase().as_str()))
+    {
+        name.pop();
+    }
+}
+
+/// The longest run of words starting here that reads as a repeat, and how
+/// many words it took
+fn parse_repeat(words: &[&str]) -> Option<(Repeat, usize)> {
+    let first = words.first()?.to_lowercase();
+    if !["every", "daily", "weekly", "monthly", "yearly"].contains(&first.as_str()) {
+        return None;
+    }
+    (1..=words.len().min(MAX_REPEAT_WORDS)).rev().find_map(|n| {
+        Repeat::parse_from_str(&words[..n].join(" "))
+            .ok()
+            .map(|repeat| (repeat, n))
+    })
+}
+
+/// A date starting here, and how many words it took. Weekdays have to be
+/// spelt out so that words like "sun" stay in the name.
+fn parse_day<Tz: TimeZone>(
+    words: &[&str],
+    tz: &Tz,
+    settings: &Settings,
+) -> Option<(DueDate, usize)> {
+    let first = words[0].to_lowercase();
+    // Dates with a time in the configured format span two words
+    if let Some(second) = words.get(1) {
+        let pair = format!("{} {}", words[0], second);
+        if let Ok(date) = utils::parse_date_in(&pair, tz, settings) {
+            return Some((date, 2));
+        }
+    }
+    if first == "next" {
+        let day = words.get(1)?.to_lowercase();
+        return is_weekday(&day)
+            .then(|| utils::parse_relative_date_in(&format!("next {}", day), tz, settings).ok())
+            .flatten()
+            .map(|date| (date, 2));
+    }
+    let relative = ["today", "tomorrow"].contains(&first.as_str())
+        || is_weekday(&first)
+        || first.starts_with("+") && first[1..].starts_with(|c: char| c.is_ascii_digit());
+    if relative {
+        return utils::parse_relative_date_in(&first, tz, settings)
+            .ok()
+            .map(|date| (date, 1));
+    }
+    utils::parse_date_in(words[0], tz, settings)
+        .ok()
+        .map(|date| (date, 1))
+}
+
+fn is_weekday(s: &str) -> bool {
+    [
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ]
+    .contains(&s)
+}
+
+/// Times like 9am, 9:30pm or 14:00. A bare number isn't taken as a time,
+/// and one like 14:00 only when `clock_time` says it follows "at" or "by".
+fn parse_time(s: &str, clock_time: bool) -> Option<NaiveTime> {
+    let lower = s.to_lowercase();
+    let (clock, offset) = if let Some(clock) = lower.strip_suffix("am") {
+        (clock, Some(0))
+    } else if let Some(clock) = lower.strip_suffix("pm") {
+        (clock, Some(12))
+    } else {
+        (lower.as_str(), None)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour, minute.parse::<u32>().ok()?),
+        None if offset.is_some() => (clock, 0),
+        None => return None,
+    };
+    if offset.is_none() && !clock_time {
+        return None;
+    }
+    let mut hour: u32 = hour.parse().ok()?;
+    if let Some(offset) = offset {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + offset;
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::default_settings as settings;
+    use chrono::{Duration, NaiveDate};
+    use chrono_tz::Europe::Berlin;
+
+    fn parse(text: &str) -> QuickAdd {
+        QuickAdd::parse_in(text, &Utc, &settings()).unwrap()
+    }
+
+    #[test]
+    fn picks_out_every_field() {
+        let quick =
+            parse("Pay rent 2025-06-30 9am every month #home !high +Bills @https://bank.example");
+        let due = NaiveDate::from_ymd_opt(2025, 6, 30)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert_eq!(quick.name, "Pay rent");
+        assert_eq!(quick.date, Some(DueDate::DateTime(due)));
+        assert_eq!(
+            quick.repeats,
+            Some(Repeat::parse_from_str("monthly").unwrap())
+        );
+        assert_eq!(quick.group.as_deref(), Some("home"));
+        assert_eq!(quick.priority, Some(Priority::High));
+        assert_eq!(quick.tags, ["bills"]);
+        assert_eq!(quick.url.as_deref(), Some("https://bank.example"));
+    }
+
+    #[test]
+    fn takes_times_in_the_given_timezone() {
+        let quick = QuickAdd::parse_in("Call mum tomorrow at 6pm", &Berlin, &settings()).unwrap();
+        let tomorrow = Utc::now().with_timezone(&Berlin).date_naive() + Duration::days(1);
+        let due = utils::resolve_local(&Berlin, &tomorrow.and_hms_opt(18, 0, 0).unwrap());
+        assert_eq!(quick.name, "Call mum");
+        assert_eq!(quick.date, Some(DueDate::DateTime(due)));
+    }
+
+    #[test]
+    fn keeps_words_that_do_not_parse() {
+        let quick = parse("Read every word # about sun !urgent +1 @home");
+        assert_eq!(quick.name, "Read every word # about sun !urgent +1 @home");
+        assert!(!quick.extracted_anything());
+    }
+
+    #[test]
+    fn only_the_first_date_is_taken() {
+        let quick = parse("Move 2025-06-30 to 2025-07-01");
+        let first = NaiveDate::from_ymd_opt(2025, 6, 30).unwrap();
+        assert_eq!(quick.name, "Move to 2025-07-01");
+        assert_eq!(quick.date, Some(DueDate::Date(first)));
+    }
+
+    #[test]
+    fn leaves_frequency_words_inside_the_name() {
+        let quick = parse("Write weekly report");
+        assert_eq!(quick.name, "Write weekly report");
+        assert_eq!(quick.repeats, None);
+
+        let quick = parse("Review monthly budget tomorrow");
+        assert_eq!(quick.name, "Review monthly budget");
+        assert_eq!(quick.repeats, None);
+        assert!(quick.date.is_some());
+    }
+
+    #[test]
+    fn takes_frequency_words_after_a_date_or_at_the_end() {
+        let weekly = Some(Repeat::parse_from_str("weekly").unwrap());
+        let quick = parse("Team sync monday weekly about roadmap");
+        assert_eq!(quick.name, "Team sync about roadmap");
+        assert_eq!(quick.repeats, weekly);
+
+        let quick = parse("Water plants weekly #home");
+        assert_eq!(quick.name, "Water plants");
+        assert_eq!(quick.repeats, weekly);
+        assert_eq!(quick.group.as_deref(), Some("home"));
+
+        let quick = parse("Write every week report");
+        assert_eq!(quick.name, "Write report");
+        assert_eq!(quick.repeats, weekly);
+    }
+
+    #[test]
+    fn needs_am_pm_or_at_for_clock_times() {
+        let quick = parse("Read chapter 3:16 of book");
+        assert_eq!(quick.name, "Read chapter 3:16 of book");
+        assert_eq!(quick.date, None);
+
+        let quick = parse("Deploy 2025-06-30 at 14:00");
+        let due = NaiveDate::from_ymd_opt(2025, 6, 30)
+            .unwrap()
+            .and_hms_opt(14, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert_eq!(quick.name, "Deploy");
+        assert_eq!(quick.date, Some(DueDate::DateTime(due)));
+    }
+
+    #[test]
+    fn needs_a_name() {
+        assert!(QuickAdd::parse_in("tomorrow #work", &Utc, &settings()).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use crate::due_date::DueDate;
+use crate::utils;
+
+/// A reminder firing some time before a task starts being due
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reminder {
+    pub minutes_before: i64,
+}
+
+impl Reminder {
+    /// Parses a comma separated list of offsets such as `30m,1d`
+    pub fn parse_list(s: &str) -> Result<Vec<Reminder>> {
+        s.split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                let offset = utils::parse_duration(s).context("Invalid reminder offset")?;
+                Ok(Reminder {
+                    minutes_before: offset.num_minutes(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn offset(&self) -> Duration {
+        Duration::minutes(self.minutes_before)
+    }
+
+    /// When the reminder goes off for a task due at `due`. Date-only tasks
+    /// count from the start of their day.
+    pub fn fire_at(&self, due: &DueDate) -> DateTime<Utc> {
+        // An offset reaching past the earliest date can never come due
+        due.start()
+            .checked_sub_signed(self.offset())
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+}
+
+impl Display for Reminder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", utils::duration_to_display_str(&self.offset()))
+    }
+}
+
+pub fn reminders_to_display_str(reminders: &[Reminder]) -> String {
+    reminders.iter().map(|r| r.to_string()).join(",")
+}
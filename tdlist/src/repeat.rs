@@ -1,58 +1,812 @@
 use crate::day_of_week::DayOfWeek;
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq)]
-pub enum Repeat {
-    Never,
+/// How far ahead to look for a month that has the requested weekday, e.g. a
+/// fifth Friday
+const MAX_MONTHS_SEARCHED: u32 = 120;
+
+/// The largest interval accepted, well past any real use and small enough
+/// that stepping through months or years can't overflow
+const MAX_INTERVAL: u32 = 1000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Frequency {
     Daily,
     Weekly,
     Monthly,
     Yearly,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Pattern {
+    /// Every `interval` days, weeks, months or years from the current date
+    Every(Frequency),
+    /// On the given days of every `interval` weeks
     DaysOfWeek(Vec<DayOfWeek>),
+    /// The nth weekday of every `interval` months, where -1 is the last one
+    NthWeekday(i8, DayOfWeek),
+    /// The last Monday to Friday of every `interval` months
+    LastBusinessDay,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RepeatEnd {
+    Never,
+    /// No occurrences after this date
+    Until(NaiveDate),
+    /// The number of occurrences left, counting the current one
+    Count(u32),
+}
+
+/// What the next occurrence is counted from when a task is completed
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RepeatFrom {
+    /// The date the task was due, e.g. rent on the 1st
+    #[default]
+    Due,
+    /// The day the task was actually done, e.g. watering plants
+    Completion,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RepeatRule {
+    pub pattern: Pattern,
+    pub interval: u32,
+    pub end: RepeatEnd,
+    pub from: RepeatFrom,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Repeat {
+    Never,
+    Rule(RepeatRule),
+}
+
+/// How repeats are written, for `--help` and the man pages. Keep in step
+/// with `Repeat::parse_from_str`.
+pub const REPEAT_HELP: &str = "\
+Repeats are written as one of:
+  never, daily, weekly, monthly or yearly
+  mon,wed,fri                       on those days every week
+  every 2 days|weeks|months|years
+  every 2 weeks on mon,thu
+  first|second|third|fourth|fifth|last mon [of every 2 months]
+  last business day [of every 2 months]
+  an iCalendar RRULE, e.g. FREQ=WEEKLY;INTERVAL=2;BYDAY=MO;COUNT=10
+
+Any of these can end with \"until 2025-06-30\" or \"10 times\" to stop the
+repeat, and with \"after completion\" to count from when the task is done
+rather than from when it was due.";
+
 impl Repeat {
+    /// Parses either the form produced by `Display` or an iCalendar RRULE.
+    ///
+    /// ```text
+    /// never | daily | weekly | monthly | yearly
+    /// mon,wed,fri
+    /// every 2 days|weeks|months|years
+    /// every 2 weeks on mon,thu
+    /// first|second|third|fourth|fifth|last mon [of every 2 months]
+    /// last business day [of every 2 months]
+    /// ... until 2025-06-30
+    /// ... 10 times
+    /// FREQ=WEEKLY;INTERVAL=2;BYDAY=MO;COUNT=10
+    /// ... after completion
+    /// ```
     pub fn parse_from_str(s: &str) -> Result<Repeat> {
-        match s.to_lowercase().as_str() {
-            "never" | "" => Ok(Repeat::Never),
-            "daily" => Ok(Repeat::Daily),
-            "weekly" => Ok(Repeat::Weekly),
-            "monthly" => Ok(Repeat::Monthly),
-            "yearly" => Ok(Repeat::Yearly),
+        let s = s.trim();
+        let (s, from) = match strip_suffix_ignore_case(s, " after completion") {
+            Some(rest) => (rest, RepeatFrom::Completion),
+            None => (s, RepeatFrom::Due),
+        };
+
+        let rrule = s.strip_prefix("RRULE:").unwrap_or(s);
+        let mut rule = if rrule.to_uppercase().contains("FREQ=") {
+            RepeatRule::parse_rrule(rrule)?
+        } else {
+            match s.to_lowercase().as_str() {
+                "never" | "" => return Ok(Repeat::Never),
+                s => RepeatRule::parse_friendly(s)?,
+            }
+        };
+        rule.from = from;
+        Ok(Repeat::Rule(rule))
+    }
+
+    pub fn from(&self) -> RepeatFrom {
+        match self {
+            Repeat::Never => RepeatFrom::Due,
+            Repeat::Rule(rule) => rule.from,
+        }
+    }
+
+    /// The rule as an iCalendar RRULE. RRULE has no notion of repeating
+    /// after completion, so that part is left out.
+    pub fn to_rrule(&self) -> Option<String> {
+        match self {
+            Repeat::Never => None,
+            Repeat::Rule(rule) => Some(rule.to_rrule()),
+        }
+    }
+
+    /// The occurrence after `date` together with the repeat to carry on
+    /// with, or `None` once the repeat has run out
+    pub fn next(&self, date: NaiveDate) -> Option<(NaiveDate, Repeat)> {
+        match self {
+            Repeat::Never => None,
+            Repeat::Rule(rule) => rule
+                .next(date)
+                .map(|(date, rule)| (date, Repeat::Rule(rule))),
+        }
+    }
+}
+
+impl RepeatRule {
+    pub fn new(pattern: Pattern) -> Self {
+        RepeatRule {
+            pattern,
+            interval: 1,
+            end: RepeatEnd::Never,
+            from: RepeatFrom::Due,
+        }
+    }
+
+    fn parse_friendly(s: &str) -> Result<RepeatRule> {
+        let (s, end) = parse_end(s)?;
+        let words: Vec<&str> = s.split_whitespace().collect();
+
+        let mut rule = match words.as_slice() {
+            ["daily"] => RepeatRule::new(Pattern::Every(Frequency::Daily)),
+            ["weekly"] => RepeatRule::new(Pattern::Every(Frequency::Weekly)),
+            ["monthly"] => RepeatRule::new(Pattern::Every(Frequency::Monthly)),
+            ["yearly"] => RepeatRule::new(Pattern::Every(Frequency::Yearly)),
+            ["every", rest @ ..] => parse_every(rest)?,
+            ["last", "business", "day", rest @ ..] => {
+                let mut rule = RepeatRule::new(Pattern::LastBusinessDay);
+                rule.interval = parse_of_every_months(rest)?;
+                rule
+            }
+            [nth, day, rest @ ..] if parse_ordinal(nth).is_some() => {
+                let nth = parse_ordinal(nth).unwrap();
+                let day = DayOfWeek::from_str(day)?;
+                let mut rule = RepeatRule::new(Pattern::NthWeekday(nth, day));
+                rule.interval = parse_of_every_months(rest)?;
+                rule
+            }
             _ => {
-                let days: Vec<Result<DayOfWeek>> = s
-                    .split(',')
-                    .map(|s| s.trim())
-                    .map(DayOfWeek::from_str)
-                    .collect();
-                if days.iter().any(|d| d.is_err()) {
-                    return Err(anyhow::anyhow!("Invalid day of the week"));
+                let days = parse_days(s).map_err(|_| anyhow!("Unrecognised repeat: {}", s))?;
+                RepeatRule::new(Pattern::DaysOfWeek(days))
+            }
+        };
+        rule.end = end;
+        Ok(rule)
+    }
+
+    fn parse_rrule(s: &str) -> Result<RepeatRule> {
+        let mut freq = None;
+        let mut interval = 1;
+        let mut by_day: Vec<String> = Vec::new();
+        let mut by_set_pos = None;
+        let mut end = RepeatEnd::Never;
+
+        for part in s.split(';').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid RRULE part: {}", part))?;
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(anyhow!("Unsupported FREQ: {}", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = check_interval(value.parse().context("Invalid INTERVAL")?)?
                 }
+                "BYDAY" => by_day = value.split(',').map(|d| d.to_uppercase()).collect(),
+                "BYSETPOS" => by_set_pos = Some(value.parse::<i8>().context("Invalid BYSETPOS")?),
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    let date =
+                        NaiveDate::parse_from_str(date, "%Y%m%d").context("Invalid UNTIL")?;
+                    end = RepeatEnd::Until(date);
+                }
+                "COUNT" => end = RepeatEnd::Count(value.parse().context("Invalid COUNT")?),
+                _ => return Err(anyhow!("Unsupported RRULE part: {}", key)),
+            }
+        }
+
+        let freq = freq.ok_or_else(|| anyhow!("RRULE is missing FREQ"))?;
+
+        let weekdays: Vec<String> = DayOfWeek::weekdays()
+            .iter()
+            .map(|d| d.to_rrule().to_string())
+            .collect();
+        let pattern = match (freq, by_day.as_slice(), by_set_pos) {
+            (_, [], None) => Pattern::Every(freq),
+            (Frequency::Weekly, days, None) => {
+                let days = days
+                    .iter()
+                    .map(|d| DayOfWeek::from_rrule(d))
+                    .try_collect()?;
+                Pattern::DaysOfWeek(days)
+            }
+            (Frequency::Monthly, [day], None) => {
+                if !day.is_ascii() {
+                    return Err(anyhow!("Invalid BYDAY: {}", day));
+                }
+                let split = day.len().saturating_sub(2);
+                if split == 0 {
+                    return Err(anyhow!(
+                        "Unsupported RRULE: {}, monthly BYDAY needs an ordinal such as 1MO or -1FR",
+                        s
+                    ));
+                }
+                let nth: i8 = day[..split].parse().context("Invalid BYDAY ordinal")?;
+                Pattern::NthWeekday(check_nth(nth)?, DayOfWeek::from_rrule(&day[split..])?)
+            }
+            (Frequency::Monthly, days, Some(-1)) if days == weekdays.as_slice() => {
+                Pattern::LastBusinessDay
+            }
+            _ => return Err(anyhow!("Unsupported RRULE: {}", s)),
+        };
+
+        Ok(RepeatRule {
+            pattern,
+            interval,
+            end,
+            from: RepeatFrom::Due,
+        })
+    }
+
+    pub fn to_rrule(&self) -> String {
+        let mut parts = match &self.pattern {
+            Pattern::Every(freq) => vec![format!("FREQ={}", freq_to_rrule(freq))],
+            Pattern::DaysOfWeek(days) => vec![
+                "FREQ=WEEKLY".to_string(),
+                format!("BYDAY={}", days.iter().map(|d| d.to_rrule()).join(",")),
+            ],
+            Pattern::NthWeekday(nth, day) => vec![
+                "FREQ=MONTHLY".to_string(),
+                format!("BYDAY={}{}", nth, day.to_rrule()),
+            ],
+            Pattern::LastBusinessDay => vec![
+                "FREQ=MONTHLY".to_string(),
+                format!(
+                    "BYDAY={}",
+                    DayOfWeek::weekdays().iter().map(|d| d.to_rrule()).join(",")
+                ),
+                "BYSETPOS=-1".to_string(),
+            ],
+        };
+        if self.interval > 1 {
+            parts.insert(1, format!("INTERVAL={}", self.interval));
+        }
+        match &self.end {
+            RepeatEnd::Never => {}
+            RepeatEnd::Until(date) => parts.push(format!("UNTIL={}", date.format("%Y%m%d"))),
+            RepeatEnd::Count(count) => parts.push(format!("COUNT={}", count)),
+        }
+        parts.join(";")
+    }
+
+    /// The occurrence after `date` together with the rule to carry on with,
+    /// or `None` once the rule has run out
+    pub fn next(&self, date: NaiveDate) -> Option<(NaiveDate, RepeatRule)> {
+        let next = self.next_date(date)?;
+        let end = match self.end {
+            RepeatEnd::Never => RepeatEnd::Never,
+            RepeatEnd::Until(until) if next <= until => RepeatEnd::Until(until),
+            RepeatEnd::Until(_) => return None,
+            RepeatEnd::Count(count) if count > 1 => RepeatEnd::Count(count - 1),
+            RepeatEnd::Count(_) => return None,
+        };
+        let rule = RepeatRule {
+            end,
+            ..self.clone()
+        };
+        Some((next, rule))
+    }
+
+    /// The first date after `date` matching the pattern, ignoring the end
+    fn next_date(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let interval = self.interval.max(1);
+        match &self.pattern {
+            Pattern::Every(Frequency::Daily) => {
+                date.checked_add_signed(Duration::days(interval.into()))
+            }
+            Pattern::Every(Frequency::Weekly) => {
+                date.checked_add_signed(Duration::weeks(interval.into()))
+            }
+            Pattern::Every(Frequency::Monthly) => date.checked_add_months(Months::new(interval)),
+            Pattern::Every(Frequency::Yearly) => {
+                date.checked_add_months(Months::new(interval.checked_mul(12)?))
+            }
+            Pattern::DaysOfWeek(days) => {
+                if days.is_empty() {
+                    return None;
+                }
+                let weekday = date.weekday().number_from_monday();
+                let later_this_week = days
+                    .iter()
+                    .map(|d| d.to_int())
+                    .filter(|&d| d > weekday)
+                    .min();
+                if let Some(d) = later_this_week {
+                    return Some(date + Duration::days((d - weekday).into()));
+                }
+                // Jump to the first matching day `interval` weeks on
+                let first = days.iter().map(|d| d.to_int()).min().unwrap();
+                let week_start = date - Duration::days((weekday - 1).into());
+                week_start
+                    .checked_add_signed(Duration::weeks(interval.into()))?
+                    .checked_add_signed(Duration::days((first - 1).into()))
+            }
+            Pattern::NthWeekday(nth, day) => self.next_in_months(date, interval, |y, m| {
+                nth_weekday_of_month(y, m, *nth, *day)
+            }),
+            Pattern::LastBusinessDay => {
+                self.next_in_months(date, interval, last_business_day_of_month)
+            }
+        }
+    }
+
+    /// Finds the next date produced by `in_month`, first in the month of
+    /// `date` and then every `interval` months after it
+    fn next_in_months<F>(&self, date: NaiveDate, interval: u32, in_month: F) -> Option<NaiveDate>
+    where
+        F: Fn(i32, u32) -> Option<NaiveDate>,
+    {
+        let month_start = date.with_day(1)?;
+        if let Some(candidate) = in_month(date.year(), date.month()) {
+            if candidate > date {
+                return Some(candidate);
+            }
+        }
+        (1..=MAX_MONTHS_SEARCHED).find_map(|i| {
+            let month = month_start.checked_add_months(Months::new(i.checked_mul(interval)?))?;
+            in_month(month.year(), month.month())
+        })
+    }
+}
+
+fn strip_suffix_ignore_case<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = s.len().checked_sub(suffix.len())?;
+    let (rest, end) = (s.get(..split)?, s.get(split..)?);
+    end.eq_ignore_ascii_case(suffix).then_some(rest)
+}
 
-                let days: Vec<DayOfWeek> =
-                    days.iter().map(|d| d.as_ref().unwrap()).cloned().collect();
+fn freq_to_rrule(freq: &Frequency) -> &'static str {
+    match freq {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+        Frequency::Yearly => "YEARLY",
+    }
+}
+
+fn nth_weekday_of_month(year: i32, month: u32, nth: i8, day: DayOfWeek) -> Option<NaiveDate> {
+    if nth > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, day.to_chrono(), nth as u8)
+    } else {
+        let last = last_day_of_month(year, month)?;
+        let back = (7 + last.weekday().num_days_from_monday()
+            - day.to_chrono().num_days_from_monday())
+            % 7;
+        Some(last - Duration::days(back.into()))
+    }
+}
 
-                Ok(Repeat::DaysOfWeek(days))
+fn last_business_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let mut date = last_day_of_month(year, month)?;
+    while date.weekday().number_from_monday() > 5 {
+        date = date.pred_opt()?;
+    }
+    Some(date)
+}
+
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, 1)?
+        .checked_add_months(Months::new(1))?
+        .pred_opt()
+}
+
+fn check_interval(interval: u32) -> Result<u32> {
+    match interval {
+        0 => Err(anyhow!("Interval must be at least 1")),
+        n if n > MAX_INTERVAL => Err(anyhow!("Interval can't be more than {}", MAX_INTERVAL)),
+        n => Ok(n),
+    }
+}
+
+fn check_nth(nth: i8) -> Result<i8> {
+    match nth {
+        1..=5 | -1 => Ok(nth),
+        _ => Err(anyhow!(
+            "Only the 1st to 5th or last weekday of a month is supported"
+        )),
+    }
+}
+
+fn parse_ordinal(s: &str) -> Option<i8> {
+    match s {
+        "first" | "1st" => Some(1),
+        "second" | "2nd" => Some(2),
+        "third" | "3rd" => Some(3),
+        "fourth" | "4th" => Some(4),
+        "fifth" | "5th" => Some(5),
+        "last" => Some(-1),
+        _ => None,
+    }
+}
+
+fn ordinal_to_str(nth: i8) -> &'static str {
+    match nth {
+        1 => "first",
+        2 => "second",
+        3 => "third",
+        4 => "fourth",
+        5 => "fifth",
+        _ => "last",
+    }
+}
+
+fn parse_days(s: &str) -> Result<Vec<DayOfWeek>> {
+    s.split(',')
+        .map(|s| s.trim())
+        .map(DayOfWeek::from_str)
+        .try_collect()
+}
+
+/// Splits a trailing `until <date>` or `<n> times` off a friendly repeat
+fn parse_end(s: &str) -> Result<(&str, RepeatEnd)> {
+    if let Some((rest, date)) = s.rsplit_once(" until ") {
+        let date =
+            NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").context("Invalid until date")?;
+        return Ok((rest, RepeatEnd::Until(date)));
+    }
+    if let Some(rest) = s.strip_suffix(" times").or(s.strip_suffix(" time")) {
+        if let Some((rest, count)) = rest.rsplit_once(' ') {
+            let count = count.parse().context("Invalid number of times")?;
+            return Ok((rest, RepeatEnd::Count(count)));
+        }
+    }
+    Ok((s, RepeatEnd::Never))
+}
+
+/// Parses what follows `every`, e.g. `2 weeks on mon,wed`
+fn parse_every(words: &[&str]) -> Result<RepeatRule> {
+    let (interval, words) = match words.first().map(|w| w.parse::<u32>()) {
+        Some(Ok(n)) => (check_interval(n)?, &words[1..]),
+        _ => (1, words),
+    };
+    let pattern = match words {
+        [unit] => Pattern::Every(parse_unit(unit)?),
+        [unit, "on", days] if parse_unit(unit)? == Frequency::Weekly => {
+            Pattern::DaysOfWeek(parse_days(days)?)
+        }
+        _ => return Err(anyhow!("Unrecognised repeat: every {}", words.join(" "))),
+    };
+    Ok(RepeatRule {
+        pattern,
+        interval,
+        end: RepeatEnd::Never,
+        from: RepeatFrom::Due,
+    })
+}
+
+fn parse_unit(s: &str) -> Result<Frequency> {
+    match s {
+        "day" | "days" => Ok(Frequency::Daily),
+        "week" | "weeks" => Ok(Frequency::Weekly),
+        "month" | "months" => Ok(Frequency::Monthly),
+        "year" | "years" => Ok(Frequency::Yearly),
+        _ => Err(anyhow!("Invalid repeat unit: {}", s)),
+    }
+}
+
+/// Parses an optional `of every <n> months` suffix into an interval
+fn parse_of_every_months(words: &[&str]) -> Result<u32> {
+    match words {
+        [] | ["of", "the", "month"] | ["of", "every", "month"] => Ok(1),
+        ["of", "every", n, "months"] => match n.parse() {
+            Ok(n) => check_interval(n),
+            _ => Err(anyhow!("Invalid number of months: {}", n)),
+        },
+        _ => Err(anyhow!("Unrecognised repeat: {}", words.join(" "))),
+    }
+}
+
+impl Display for RepeatRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = |freq: &Frequency| match freq {
+            Frequency::Daily => "days",
+            Frequency::Weekly => "weeks",
+            Frequency::Monthly => "months",
+            Frequency::Yearly => "years",
+        };
+        let of_every = |interval: u32| match interval {
+            1 => String::new(),
+            n => format!(" of every {} months", n),
+        };
+
+        let mut s = match (&self.pattern, self.interval) {
+            (Pattern::Every(Frequency::Daily), 1) => "Daily".to_string(),
+            (Pattern::Every(Frequency::Weekly), 1) => "Weekly".to_string(),
+            (Pattern::Every(Frequency::Monthly), 1) => "Monthly".to_string(),
+            (Pattern::Every(Frequency::Yearly), 1) => "Yearly".to_string(),
+            (Pattern::Every(freq), n) => format!("every {} {}", n, unit(freq)),
+            (Pattern::DaysOfWeek(days), 1) => days.iter().map(|d| d.to_string()).join(","),
+            (Pattern::DaysOfWeek(days), n) => format!(
+                "every {} weeks on {}",
+                n,
+                days.iter().map(|d| d.to_string()).join(",")
+            ),
+            (Pattern::NthWeekday(nth, day), n) => {
+                format!("{} {}{}", ordinal_to_str(*nth), day, of_every(n))
             }
+            (Pattern::LastBusinessDay, n) => format!("last business day{}", of_every(n)),
+        };
+        match &self.end {
+            RepeatEnd::Never => {}
+            RepeatEnd::Until(date) => s.push_str(&format!(" until {}", date.format("%Y-%m-%d"))),
+            RepeatEnd::Count(1) => s.push_str(" 1 time"),
+            RepeatEnd::Count(count) => s.push_str(&format!(" {} times", count)),
+        }
+        if self.from == RepeatFrom::Completion {
+            s.push_str(" after completion");
         }
+        f.pad(&s)
     }
 }
 
 impl Display for Repeat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Repeat::Never => write!(f, "Never"),
-            Repeat::Daily => write!(f, "Daily"),
-            Repeat::Weekly => write!(f, "Weekly"),
-            Repeat::Monthly => write!(f, "Monthly"),
-            Repeat::Yearly => write!(f, "Yearly"),
-            Repeat::DaysOfWeek(days) => {
-                let days = days.iter().map(|d| d.to_string()).join(",");
-                write!(f, "{}", days)
+            Repeat::Never => f.pad("Never"),
+            Repeat::Rule(rule) => rule.fmt(f),
+        }
+    }
+}
+
+impl Serialize for Repeat {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Deserialize)]
+enum LegacyRepeat {
+    DaysOfWeek(Vec<DayOfWeek>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RepeatRepr {
+    Str(String),
+    Legacy(LegacyRepeat),
+}
+
+impl<'de> Deserialize<'de> for Repeat {
+    /// Repeats are stored in their display form. Databases from before
+    /// repeat rules stored days of the week as a list.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match RepeatRepr::deserialize(deserializer)? {
+            RepeatRepr::Str(s) => Repeat::parse_from_str(&s).map_err(serde::de::Error::custom),
+            RepeatRepr::Legacy(LegacyRepeat::DaysOfWeek(days)) => {
+                Ok(Repeat::Rule(RepeatRule::new(Pattern::DaysOfWeek(days))))
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: Pattern, interval: u32, end: RepeatEnd, from: RepeatFrom) -> RepeatRule {
+        RepeatRule {
+            pattern,
+            interval,
+            end,
+            from,
+        }
+    }
+
+    /// Every pattern with every kind of end and start, at the intervals each
+    /// pattern can be written with
+    fn all_rules() -> Vec<RepeatRule> {
+        let patterns = [
+            Pattern::Every(Frequency::Daily),
+            Pattern::Every(Frequency::Weekly),
+            Pattern::Every(Frequency::Monthly),
+            Pattern::Every(Frequency::Yearly),
+            Pattern::DaysOfWeek(vec![DayOfWeek::Monday]),
+            Pattern::DaysOfWeek(vec![
+                DayOfWeek::Monday,
+                DayOfWeek::Wednesday,
+                DayOfWeek::Friday,
+            ]),
+            Pattern::NthWeekday(1, DayOfWeek::Tuesday),
+            Pattern::NthWeekday(5, DayOfWeek::Friday),
+            Pattern::NthWeekday(-1, DayOfWeek::Sunday),
+            Pattern::LastBusinessDay,
+        ];
+        let ends = [
+            RepeatEnd::Never,
+            RepeatEnd::Until(NaiveDate::from_ymd_opt(2025, 6, 30).unwrap()),
+            RepeatEnd::Count(1),
+            RepeatEnd::Count(10),
+        ];
+        let froms = [RepeatFrom::Due, RepeatFrom::Completion];
+
+        let mut rules = Vec::new();
+        for pattern in &patterns {
+            for interval in [1, 3] {
+                for end in &ends {
+                    for from in froms {
+                        rules.push(rule(pattern.clone(), interval, end.clone(), from));
+                    }
+                }
+            }
+        }
+        rules
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for rule in all_rules() {
+            let repeat = Repeat::Rule(rule);
+            let shown = repeat.to_string();
+            let parsed = Repeat::parse_from_str(&shown)
+                .unwrap_or_else(|e| panic!("{} didn't parse: {}", shown, e));
+            assert_eq!(parsed, repeat, "{}", shown);
+        }
+        assert_eq!(
+            Repeat::parse_from_str(&Repeat::Never.to_string()).unwrap(),
+            Repeat::Never
+        );
+    }
+
+    #[test]
+    fn rrule_round_trips() {
+        for rule in all_rules() {
+            let rrule = rule.to_rrule();
+            let mut parsed = RepeatRule::parse_rrule(&rrule)
+                .unwrap_or_else(|e| panic!("{} didn't parse: {}", rrule, e));
+            // RRULE can't say "after completion", the suffix carries it
+            assert_eq!(parsed.from, RepeatFrom::Due, "{}", rrule);
+            parsed.from = rule.from;
+            assert_eq!(parsed, rule, "{}", rrule);
+
+            let suffixed = match rule.from {
+                RepeatFrom::Due => rrule.clone(),
+                RepeatFrom::Completion => format!("{} after completion", rrule),
+            };
+            let repeat = Repeat::parse_from_str(&suffixed).unwrap();
+            assert_eq!(repeat, Repeat::Rule(rule), "{}", suffixed);
+        }
+    }
+
+    #[test]
+    fn parses_friendly_forms() {
+        let parse = |s| match Repeat::parse_from_str(s).unwrap() {
+            Repeat::Rule(rule) => rule,
+            Repeat::Never => panic!("{} parsed as never", s),
+        };
+        assert_eq!(
+            parse("every 2 weeks on mon,thu until 2025-06-30"),
+            rule(
+                Pattern::DaysOfWeek(vec![DayOfWeek::Monday, DayOfWeek::Thursday]),
+                2,
+                RepeatEnd::Until(NaiveDate::from_ymd_opt(2025, 6, 30).unwrap()),
+                RepeatFrom::Due
+            )
+        );
+        assert_eq!(
+            parse("Every 3 Days After Completion"),
+            rule(
+                Pattern::Every(Frequency::Daily),
+                3,
+                RepeatEnd::Never,
+                RepeatFrom::Completion
+            )
+        );
+        assert_eq!(
+            parse("RRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=4"),
+            rule(
+                Pattern::NthWeekday(-1, DayOfWeek::Friday),
+                1,
+                RepeatEnd::Count(4),
+                RepeatFrom::Due
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_rrules() {
+        let error = |s| Repeat::parse_from_str(s).unwrap_err().to_string();
+        assert!(error("FREQ=MONTHLY;BYDAY=MO").starts_with("Unsupported RRULE"));
+        assert!(error("FREQ=MONTHLY;BYDAY=MO,WE").starts_with("Unsupported RRULE"));
+        assert!(error("FREQ=HOURLY").starts_with("Unsupported FREQ"));
+        assert!(error("FREQ=MONTHLY;BYDAY=6MO").contains("1st to 5th"));
+        assert!(error("FREQ=DAILY;INTERVAL=0").contains("at least 1"));
+        assert!(error("FREQ=MONTHLY;BYDAY=1ÉA").starts_with("Invalid BYDAY"));
+    }
+
+    #[test]
+    fn caps_intervals() {
+        assert!(Repeat::parse_from_str("every 1000 years").is_ok());
+        assert!(Repeat::parse_from_str("every 400000000 years").is_err());
+        assert!(Repeat::parse_from_str("last fri of every 40000000 months").is_err());
+        assert!(Repeat::parse_from_str("FREQ=YEARLY;INTERVAL=400000000").is_err());
+        assert!(Repeat::parse_from_str("every 0 days").is_err());
+    }
+
+    #[test]
+    fn runs_out_instead_of_overflowing() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let huge = |pattern| RepeatRule {
+            interval: u32::MAX,
+            ..RepeatRule::new(pattern)
+        };
+        assert_eq!(huge(Pattern::Every(Frequency::Yearly)).next(date), None);
+        assert_eq!(
+            huge(Pattern::LastBusinessDay).next(date),
+            Some((
+                NaiveDate::from_ymd_opt(2025, 6, 30).unwrap(),
+                huge(Pattern::LastBusinessDay)
+            ))
+        );
+        let last_day = NaiveDate::from_ymd_opt(2025, 6, 30).unwrap();
+        assert_eq!(huge(Pattern::LastBusinessDay).next(last_day), None);
+        assert_eq!(
+            huge(Pattern::DaysOfWeek(vec![DayOfWeek::Monday])).next(date),
+            None
+        );
+    }
+
+    #[test]
+    fn steps_to_next_occurrence() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let next = |s, from| {
+            Repeat::parse_from_str(s)
+                .unwrap()
+                .next(from)
+                .map(|(d, _)| d)
+        };
+
+        assert_eq!(next("mon,fri", date(2025, 6, 3)), Some(date(2025, 6, 6)));
+        assert_eq!(
+            next("every 2 weeks on mon", date(2025, 6, 2)),
+            Some(date(2025, 6, 16))
+        );
+        assert_eq!(next("monthly", date(2025, 1, 31)), Some(date(2025, 2, 28)));
+        // October 2025 ends on a Friday, May 2025 on a Saturday
+        assert_eq!(
+            next("last business day", date(2025, 10, 1)),
+            Some(date(2025, 10, 31))
+        );
+        assert_eq!(
+            next("last business day", date(2025, 5, 1)),
+            Some(date(2025, 5, 30))
+        );
+        // Months without a fifth Friday are skipped
+        assert_eq!(next("fifth fri", date(2025, 6, 1)), Some(date(2025, 8, 29)));
+        assert_eq!(next("daily 1 time", date(2025, 6, 1)), None);
+        assert_eq!(next("daily until 2025-06-02", date(2025, 6, 2)), None);
+    }
+}
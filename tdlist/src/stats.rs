@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::app::Id;
+use crate::due_date::DueDate;
+use crate::history::HistoryKind;
+use crate::repeat::Repeat;
+use crate::task::Task;
+use crate::utils;
+
+#[derive(Serialize)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct GroupStats {
+    pub group: Option<String>,
+    pub open: usize,
+    pub overdue: usize,
+    pub completed: usize,
+}
+
+#[derive(Serialize)]
+pub struct Streak {
+    pub id: Id,
+    pub name: String,
+    /// Occurrences completed in a row up to the latest one
+    pub current: usize,
+    pub longest: usize,
+}
+
+/// Throughput over the `days` up to today, in the viewer's timezone
+#[derive(Serialize)]
+pub struct Stats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub completed: usize,
+    pub completed_per_day: Vec<DayCount>,
+    /// Keyed by the Monday each week starts on
+    pub completed_per_week: Vec<DayCount>,
+    pub open: usize,
+    pub overdue: usize,
+    pub late_completions: usize,
+    /// How late completions were on average, counting ones on time as zero
+    pub average_lateness_minutes: Option<i64>,
+    pub groups: Vec<GroupStats>,
+    pub streaks: Vec<Streak>,
+}
+
+struct Completion {
+    at: DateTime<Utc>,
+    due: DueDate,
+}
+
+/// Every completion of the task. Tasks completed before history was kept
+/// only know their latest completion.
+fn completions(task: &Task) -> Vec<Completion> {
+    let from_history: Vec<Completion> = task
+        .history
+        .iter()
+        .filter(|e| e.kind == HistoryKind::Completed)
+        .map(|e| Completion {
+            at: e.at,
+            due: e.due,
+        })
+        .collect();
+    if !from_history.is_empty() {
+        return from_history;
+    }
+    match (task.complete, task.completed_at) {
+        (true, Some(at)) => vec![Completion { at, due: task.date }],
+        _ => vec![],
+    }
+}
+
+/// Completions in a row. Skipping an occurrence, or doing it only once the
+/// next one was already due, breaks the run, and so does being overdue now.
+fn streaks(task: &Task, now: DateTime<Utc>) -> Streak {
+    let tz = task.timezone.as_ref();
+    let mut current = 0;
+    let mut longest = 0;
+    for entry in &task.history {
+        let missed_next = task
+            .repeats
+            .next(entry.due.date_in(tz))
+            .is_some_and(|(next, _)| entry.due.with_date(next, tz).is_overdue(entry.at));
+        match entry.kind {
+            HistoryKind::Completed if missed_next => current = 1,
+            HistoryKind::Completed => current += 1,
+            HistoryKind::Skipped => current = 0,
+        }
+        longest = longest.max(current);
+    }
+    if !task.complete && task.date.is_overdue(now) {
+        current = 0;
+    }
+    Streak {
+        id: task.id.unwrap(),
+        name: task.name.clone(),
+        current,
+        longest,
+    }
+}
+
+pub fn compute(tasks: &HashMap<Id, Task>, now: DateTime<Utc>, days: u32) -> Stats {
+    let to = utils::to_local(&now).date_naive();
+    let from = to - Duration::days(i64::from(days.max(1)) - 1);
+    let week_start = |d: NaiveDate| d - Duration::days(d.weekday().num_days_from_monday().into());
+
+    let mut per_day: BTreeMap<NaiveDate, usize> = (0..days.max(1))
+        .map(|i| (from + Duration::days(i.into()), 0))
+        .collect();
+    let mut per_week: BTreeMap<NaiveDate, usize> =
+        per_day.keys().map(|&d| (week_start(d), 0)).collect();
+    let mut groups: BTreeMap<Option<String>, GroupStats> = BTreeMap::new();
+    let mut lateness = Vec::new();
+    let mut completed = 0;
+
+    for task in tasks.values() {
+        let group = groups
+            .entry(task.group.clone())
+            .or_insert_with(|| GroupStats {
+                group: task.group.clone(),
+                open: 0,
+                overdue: 0,
+                completed: 0,
+            });
+        if !task.complete {
+            group.open += 1;
+            if task.date.is_overdue(now) {
+                group.overdue += 1;
+            }
+        }
+
+        for completion in completions(task) {
+            let day = utils::to_local(&completion.at).date_naive();
+            if day < from || day > to {
+                continue;
+            }
+            completed += 1;
+            group.completed += 1;
+            *per_day.entry(day).or_default() += 1;
+            *per_week.entry(week_start(day)).or_default() += 1;
+            let late = completion.at - completion.due.deadline();
+            lateness.push(late.max(Duration::zero()));
+        }
+    }
+
+    let mut streaks: Vec<Streak> = tasks
+        .values()
+        .filter(|t| t.repeats != Repeat::Never)
+        .map(|t| streaks(t, now))
+        .collect();
+    streaks.sort_by(|a, b| b.current.cmp(&a.current).then_with(|| a.id.cmp(&b.id)));
+
+    let average_lateness_minutes = if lateness.is_empty() {
+        None
+    } else {
+        let total: i64 = lateness.iter().map(|l| l.num_minutes()).sum();
+        Some(total / lateness.len() as i64)
+    };
+
+    let groups: Vec<GroupStats> = groups.into_values().collect();
+    Stats {
+        from,
+        to,
+        completed,
+        completed_per_day: per_day
+            .into_iter()
+            .map(|(date, count)| DayCount { date, count })
+            .collect(),
+        completed_per_week: per_week
+            .into_iter()
+            .map(|(date, count)| DayCount { date, count })
+            .collect(),
+        open: groups.iter().map(|g| g.open).sum(),
+        overdue: groups.iter().map(|g| g.overdue).sum(),
+        late_completions: lateness.iter().filter(|l| **l > Duration::zero()).count(),
+        average_lateness_minutes,
+        groups,
+        streaks,
+    }
+}
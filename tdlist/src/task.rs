@@ -1,38 +1,210 @@
-use crate::repeat::Repeat;
-use chrono::{Date, DateTime, Local, TimeZone};
+use crate::due_date::DueDate;
+use crate::group;
+use crate::history::{HistoryEntry, HistoryKind};
+use crate::link::Link;
+use crate::priority::Priority;
+use crate::reminder::Reminder;
+use crate::repeat::{Repeat, RepeatFrom};
+use crate::time_entry::TimeEntry;
+use crate::utils;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
-pub fn serialize_dt<S>(date: &DateTime<Local>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: serde::Serializer,
-{
-    let s = date.format("%+").to_string();
-    serializer.serialize_str(&s)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DueDateRepr {
+    Current(DueDate),
+    Legacy(String),
 }
 
-pub fn deserialize_dt<'de, D>(deserializer: D) -> Result<DateTime<Local>, D::Error>
+/// Reads due dates written before date-only tasks were explicit. Those were
+/// stored as a timestamp, with 23:59 standing in for "no time".
+pub fn deserialize_due<'de, D>(deserializer: D) -> Result<DueDate, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    let dt = Local.datetime_from_str(&s, "%+").unwrap();
-    Ok(dt)
+    match DueDateRepr::deserialize(deserializer)? {
+        DueDateRepr::Current(due) => Ok(due),
+        DueDateRepr::Legacy(s) => {
+            let dt = DateTime::parse_from_rfc3339(&s).map_err(serde::de::Error::custom)?;
+            let local = utils::to_local(&dt.with_timezone(&Utc));
+            let is_sentinel = |t: NaiveTime| t.hour() == 23 && t.minute() == 59;
+            if is_sentinel(dt.time()) {
+                Ok(DueDate::Date(dt.date_naive()))
+            } else if is_sentinel(local.time()) {
+                Ok(DueDate::Date(local.date_naive()))
+            } else {
+                Ok(DueDate::DateTime(dt.with_timezone(&Utc)))
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Task {
     pub id: Option<usize>,
     pub name: String,
-    #[serde(serialize_with = "serialize_dt", deserialize_with = "deserialize_dt")]
-    pub date: DateTime<Local>,
+    #[serde(deserialize_with = "deserialize_due")]
+    pub date: DueDate,
+    /// The timezone the date was entered in, when it differs from the viewer's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    /// The task is hidden from listings until this date
+    #[serde(default)]
+    pub scheduled: Option<DueDate>,
     pub repeats: Repeat,
     pub group: Option<String>,
     pub description: Option<String>,
-    pub url: Option<String>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// Lower-cased labels, unlike groups a task can have any number of them
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    /// The single url tasks had before links, moved into `links` on load
+    #[serde(default, rename = "url", skip_serializing)]
+    legacy_url: Option<String>,
     pub complete: bool,
+    /// Unknown for tasks created before timestamps were kept
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Utc>>,
+    /// When the task, or its latest occurrence if it repeats, was completed
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Every completed or skipped occurrence, oldest first
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    #[serde(default)]
+    pub reminders: Vec<Reminder>,
+    /// Fire times of reminders that have already been delivered
+    #[serde(default)]
+    pub fired_reminders: Vec<DateTime<Utc>>,
+    /// How long the task is expected to take
+    #[serde(default)]
+    pub estimate_minutes: Option<i64>,
+    /// Time spent on the task, oldest first
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
 }
 
 impl Task {
+    /// The due date after the current one if the task is done at
+    /// `completed_at`, along with what the repeat becomes after it. `None`
+    /// when the task doesn't repeat again.
+    pub fn next_occurrence(&self, completed_at: DateTime<Utc>) -> Option<(DueDate, Repeat)> {
+        let tz = self.timezone.as_ref();
+        let from = match self.repeats.from() {
+            RepeatFrom::Due => self.date.date_in(tz),
+            RepeatFrom::Completion => DueDate::DateTime(completed_at).date_in(tz),
+        };
+        let (date, repeats) = self.repeats.next(from)?;
+        Some((self.date.with_date(date, tz), repeats))
+    }
+
+    /// Marks the task as done. Repeating tasks move on to their next
+    /// occurrence instead, carrying their scheduled date along.
+    pub fn complete(&mut self, completed_at: DateTime<Utc>) {
+        self.completed_at = Some(completed_at);
+        self.history.push(HistoryEntry {
+            kind: HistoryKind::Completed,
+            at: completed_at,
+            due: self.date,
+        });
+        match self.next_occurrence(completed_at) {
+            Some((date, repeats)) => {
+                self.move_to(date);
+                self.repeats = repeats;
+            }
+            None => self.complete = true,
+        }
+    }
+
+    /// Moves a repeating task on to its next occurrence without completing
+    /// the current one
+    pub fn skip(&mut self, skipped_at: DateTime<Utc>) -> Result<()> {
+        if self.repeats == Repeat::Never {
+            return Err(anyhow!("Only repeating tasks can be skipped"));
+        }
+        let (date, repeats) = self
+            .next_occurrence(skipped_at)
+            .ok_or_else(|| anyhow!("Task has no occurrences left to skip to"))?;
+        self.history.push(HistoryEntry {
+            kind: HistoryKind::Skipped,
+            at: skipped_at,
+            due: self.date,
+        });
+        self.move_to(date);
+        self.repeats = repeats;
+        Ok(())
+    }
+
+    /// Pushes the due date out by `by`
+    pub fn snooze(&mut self, by: Duration) -> Result<()> {
+        let date = self.date.shifted(by, self.timezone.as_ref())?;
+        self.move_to(date);
+        Ok(())
+    }
+
+    /// Moves the task to another day. A timed task keeps its time of day
+    /// when `to` has none.
+    pub fn postpone(&mut self, to: DueDate) {
+        let date = match to {
+            DueDate::Date(day) => self.date.with_date(day, self.timezone.as_ref()),
+            DueDate::DateTime(_) => to,
+        };
+        self.move_to(date);
+    }
+
+    /// Changes the due date, carrying the scheduled date along by the same
+    /// number of days
+    pub fn move_to(&mut self, date: DueDate) {
+        let tz = self.timezone.as_ref();
+        if let Some(scheduled) = self.scheduled {
+            let shift = date.date_in(tz) - self.date.date_in(tz);
+            let day = scheduled.date_in(tz) + shift;
+            self.scheduled = Some(scheduled.with_date(day, tz));
+        }
+        self.date = date;
+    }
+
+    pub fn uncomplete(&mut self) {
+        self.complete = false;
+        self.completed_at = None;
+        if let Some(i) = self
+            .history
+            .iter()
+            .rposition(|e| e.kind == HistoryKind::Completed && e.due == self.date)
+        {
+            self.history.remove(i);
+        }
+    }
+
+    /// Upgrades fields stored by older versions
+    pub fn migrate(&mut self) {
+        if let Some(url) = self.legacy_url.take() {
+            self.links.insert(0, Link::url(url));
+        }
+        // Groups used to be stored as typed, so "Work" and "work" could differ
+        self.group = self.group.as_deref().and_then(group::normalize);
+    }
+
+    /// The task's links and attachments, or any urls found in its
+    /// description when it has none
+    pub fn link_targets(&self) -> Vec<String> {
+        if !self.links.is_empty() {
+            return self.links.iter().map(|l| l.target_str()).collect();
+        }
+        self.description
+            .as_deref()
+            .map(utils::extract_urls)
+            .unwrap_or_default()
+    }
+
     pub fn set_id(&mut self, id: Option<usize>) {
         self.id = id;
     }
@@ -41,24 +213,106 @@ impl Task {
         self.name = name;
     }
 
-    pub fn set_date(&mut self, date: DateTime<Local>) {
+    pub fn set_date(&mut self, date: DueDate) {
         self.date = date;
     }
 
+    pub fn set_scheduled(&mut self, scheduled: DueDate) {
+        self.scheduled = Some(scheduled);
+    }
+
+    pub fn set_timezone(&mut self, timezone: Tz) {
+        self.timezone = Some(timezone);
+    }
+
     pub fn set_repeats(&mut self, repeats: Repeat) {
         self.repeats = repeats;
     }
 
+    /// Groups are stored normalized so "Work" and "work" are the same
+    /// group. A blank group clears it.
     pub fn set_group(&mut self, group: String) {
-        self.group = Some(group);
+        self.group = group::normalize(&group);
+    }
+
+    /// Whether the task is in `group` or one of the groups nested under it
+    pub fn in_group(&self, group: &str) -> bool {
+        match (
+            self.group.as_deref().and_then(group::normalize),
+            group::normalize(group),
+        ) {
+            (Some(own), Some(group)) => group::is_within(&own, &group),
+            _ => false,
+        }
     }
 
     pub fn set_description(&mut self, description: String) {
         self.description = Some(description);
     }
 
-    pub fn set_url(&mut self, url: String) {
-        self.url = Some(url);
+    pub fn set_estimate(&mut self, estimate: Duration) {
+        self.estimate_minutes = Some(estimate.num_minutes());
+    }
+
+    pub fn estimate(&self) -> Option<Duration> {
+        self.estimate_minutes.map(Duration::minutes)
+    }
+
+    pub fn running_entry(&self) -> Option<&TimeEntry> {
+        self.time_entries.iter().find(|e| e.is_running())
+    }
+
+    /// Starts logging time on the task
+    pub fn start_timer(&mut self, at: DateTime<Utc>) -> Result<()> {
+        if self.running_entry().is_some() {
+            return Err(anyhow!("{} is already being timed", self.name));
+        }
+        self.time_entries.push(TimeEntry {
+            start: at,
+            end: None,
+        });
+        Ok(())
+    }
+
+    /// Stops the running timer, returning how long it ran
+    pub fn stop_timer(&mut self, at: DateTime<Utc>) -> Option<Duration> {
+        let entry = self.time_entries.iter_mut().find(|e| e.is_running())?;
+        entry.end = Some(at.max(entry.start));
+        Some(entry.duration(at))
+    }
+
+    /// Time logged between `from` and `to`, counting a running timer up to
+    /// `now`
+    pub fn time_logged(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Duration {
+        self.time_entries
+            .iter()
+            .map(|e| e.overlap(from, to, now))
+            .fold(Duration::zero(), |total, d| total + d)
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = Some(priority);
+    }
+
+    /// Adds a tag unless the task already has it
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+    }
+
+    pub fn add_link(&mut self, link: Link) {
+        self.links.push(link);
+    }
+
+    pub fn set_reminders(&mut self, reminders: Vec<Reminder>) {
+        self.reminders = reminders;
     }
 }
 
@@ -67,12 +321,166 @@ impl Default for Task {
         Self {
             id: None,
             name: "".to_string(),
-            date: Local::now(),
+            date: utils::get_today(),
+            timezone: None,
+            scheduled: None,
             repeats: Repeat::Never,
             group: None,
             description: None,
-            url: None,
+            priority: None,
+            tags: Vec::new(),
+            links: Vec::new(),
+            legacy_url: None,
             complete: false,
+            created_at: None,
+            modified_at: None,
+            completed_at: None,
+            history: Vec::new(),
+            reminders: Vec::new(),
+            fired_reminders: Vec::new(),
+            estimate_minutes: None,
+            time_entries: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    #[derive(Deserialize)]
+    struct Stored {
+        #[serde(deserialize_with = "deserialize_due")]
+        date: DueDate,
+    }
+
+    fn load(json: &str) -> DueDate {
+        serde_json::from_str::<Stored>(json).unwrap().date
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn reads_current_due_dates() {
+        let date = DueDate::Date(day(2024, 3, 10));
+        let json = format!("{{\"date\":{}}}", serde_json::to_string(&date).unwrap());
+        assert_eq!(load(&json), date);
+
+        let timed = DueDate::DateTime(Utc.with_ymd_and_hms(2024, 3, 10, 14, 30, 0).unwrap());
+        let json = format!("{{\"date\":{}}}", serde_json::to_string(&timed).unwrap());
+        assert_eq!(load(&json), timed);
+    }
+
+    #[test]
+    fn reads_the_legacy_no_time_sentinel_as_a_date() {
+        assert_eq!(
+            load(r#"{"date":"2024-03-10T23:59:00+01:00"}"#),
+            DueDate::Date(day(2024, 3, 10))
+        );
+    }
+
+    #[test]
+    fn reads_legacy_times_as_instants() {
+        assert_eq!(
+            load(r#"{"date":"2024-03-10T15:30:00+01:00"}"#),
+            DueDate::DateTime(Utc.with_ymd_and_hms(2024, 3, 10, 14, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn reads_the_sentinel_in_local_time_when_the_offset_differs() {
+        // Written as 23:59 local time but stored with another offset, as
+        // happens when the database moved between machines
+        let local = Local
+            .from_local_datetime(&day(2024, 3, 10).and_hms_opt(23, 59, 0).unwrap())
+            .earliest()
+            .unwrap();
+        let offset =
+            chrono::FixedOffset::east_opt(local.offset().local_minus_utc() + 3600).unwrap();
+        let stored = local.with_timezone(&offset).to_rfc3339();
+        assert_eq!(
+            load(&format!("{{\"date\":\"{}\"}}", stored)),
+            DueDate::Date(day(2024, 3, 10))
+        );
+    }
+
+    fn repeating(repeats: &str, date: DueDate) -> Task {
+        let mut task = Task::default();
+        task.set_name("chore".to_string());
+        task.set_date(date);
+        task.set_timezone(chrono_tz::UTC);
+        task.set_repeats(Repeat::parse_from_str(repeats).unwrap());
+        task
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn repeats_from_the_due_date() {
+        let mut task = repeating("weekly", DueDate::Date(day(2025, 6, 2)));
+        task.complete(at(2025, 6, 5, 12));
+        assert!(!task.complete);
+        assert_eq!(task.date, DueDate::Date(day(2025, 6, 9)));
+        assert_eq!(task.history.len(), 1);
+        assert_eq!(task.history[0].due, DueDate::Date(day(2025, 6, 2)));
+    }
+
+    #[test]
+    fn repeats_from_completion() {
+        let mut task = repeating(
+            "every 3 days after completion",
+            DueDate::Date(day(2025, 6, 2)),
+        );
+        task.complete(at(2025, 6, 5, 12));
+        assert_eq!(task.date, DueDate::Date(day(2025, 6, 8)));
+    }
+
+    #[test]
+    fn keeps_the_time_of_day_across_dst() {
+        // 09:00 in Berlin, before and after clocks go forward on the 30th
+        let mut task = repeating("weekly", DueDate::DateTime(at(2025, 3, 24, 8)));
+        task.set_timezone(chrono_tz::Europe::Berlin);
+        task.complete(at(2025, 3, 24, 9));
+        assert_eq!(task.date, DueDate::DateTime(at(2025, 3, 31, 7)));
+    }
+
+    #[test]
+    fn carries_the_scheduled_date_along() {
+        let mut task = repeating("weekly", DueDate::Date(day(2025, 6, 6)));
+        task.set_scheduled(DueDate::Date(day(2025, 6, 2)));
+        task.complete(at(2025, 6, 6, 12));
+        assert_eq!(task.date, DueDate::Date(day(2025, 6, 13)));
+        assert_eq!(task.scheduled, Some(DueDate::Date(day(2025, 6, 9))));
+    }
+
+    #[test]
+    fn counts_down_and_then_completes() {
+        let mut task = repeating("daily 2 times", DueDate::Date(day(2025, 6, 2)));
+        task.complete(at(2025, 6, 2, 12));
+        assert!(!task.complete);
+        assert_eq!(task.date, DueDate::Date(day(2025, 6, 3)));
+        assert_eq!(
+            task.repeats,
+            Repeat::parse_from_str("daily 1 time").unwrap()
+        );
+
+        task.complete(at(2025, 6, 3, 12));
+        assert!(task.complete);
+        assert_eq!(task.date, DueDate::Date(day(2025, 6, 3)));
+        assert_eq!(task.history.len(), 2);
+    }
+
+    #[test]
+    fn stops_at_the_until_date() {
+        let mut task = repeating("weekly until 2025-06-10", DueDate::Date(day(2025, 6, 2)));
+        task.complete(at(2025, 6, 2, 12));
+        assert_eq!(task.date, DueDate::Date(day(2025, 6, 9)));
+        task.complete(at(2025, 6, 9, 12));
+        assert!(task.complete);
+    }
+}
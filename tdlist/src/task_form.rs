@@ -1,18 +1,33 @@
 use crate::configuration::Settings;
+use crate::link::Link;
+use crate::priority::Priority;
+use crate::reminder::Reminder;
 use crate::repeat::Repeat;
 use crate::task::Task;
 use crate::utils;
 use anyhow::{anyhow, Context, Result};
+use chrono_tz::Tz;
+use std::path::Path;
 
 #[derive(Default)]
 pub struct TaskForm {
     pub id: Option<usize>,
     pub name: String,
     pub date: String,
+    pub timezone: String,
+    pub scheduled: String,
     pub repeats: String,
     pub group: String,
     pub description: String,
+    pub priority: String,
+    pub tags: Vec<String>,
     pub url: String,
+    /// Links as `label=url` or a bare url
+    pub links: Vec<String>,
+    /// Paths of local files to reference
+    pub attachments: Vec<String>,
+    pub reminders: String,
+    pub estimate: String,
 }
 
 impl TaskForm {
@@ -22,12 +37,44 @@ impl TaskForm {
         }
         let mut task = Task::default();
         let repeat = Repeat::parse_from_str(&self.repeats).context("Invalid repeat format")?;
-        let date = utils::parse_date(&self.date, settings).unwrap_or(utils::get_today());
+        let reminders = Reminder::parse_list(&self.reminders)?;
+        let priority = if self.priority.is_empty() {
+            None
+        } else {
+            Some(self.priority.parse::<Priority>()?)
+        };
+        let timezone = if self.timezone.is_empty() {
+            None
+        } else {
+            let tz = self.timezone.parse::<Tz>().map_err(|e| anyhow!(e));
+            Some(tz.context("Invalid timezone")?)
+        };
+        let parse_date = |s: &str| match &timezone {
+            Some(tz) => utils::parse_relative_date_in(s, tz, settings),
+            None => utils::parse_relative_date(s, settings),
+        };
+        let date = if self.date.is_empty() {
+            utils::get_today()
+        } else {
+            parse_date(&self.date).context("Invalid date")?
+        };
+        let scheduled = if self.scheduled.is_empty() {
+            None
+        } else {
+            let scheduled = parse_date(&self.scheduled);
+            Some(scheduled.context("Invalid scheduled date")?)
+        };
         // let a: usize = 3;
         // task.set_id(Some(a));
         task.set_id(self.id);
         task.set_name(self.name.clone());
         task.set_date(date);
+        if let Some(scheduled) = scheduled {
+            task.set_scheduled(scheduled);
+        }
+        if let Some(tz) = timezone {
+            task.set_timezone(tz);
+        }
         task.set_repeats(repeat);
         if !self.group.is_empty() {
             task.set_group(self.group.clone());
@@ -35,8 +82,24 @@ impl TaskForm {
         if !self.description.is_empty() {
             task.set_description(self.description.clone());
         }
+        if let Some(priority) = priority {
+            task.set_priority(priority);
+        }
+        for tag in &self.tags {
+            task.add_tag(tag);
+        }
         if !self.url.is_empty() {
-            task.set_url(self.url.clone());
+            task.add_link(Link::url(self.url.clone()));
+        }
+        for link in &self.links {
+            task.add_link(Link::parse(link)?);
+        }
+        for path in &self.attachments {
+            task.add_link(Link::file(Path::new(path), None)?);
+        }
+        task.set_reminders(reminders);
+        if !self.estimate.is_empty() {
+            task.set_estimate(utils::parse_duration(&self.estimate).context("Invalid estimate")?);
         }
         Ok(task)
     }
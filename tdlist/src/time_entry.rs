@@ -0,0 +1,93 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A stretch of time spent on a task. Entries without an end are still
+/// running.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeEntry {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// How much of the entry falls between `from` and `to`, counting a
+    /// running entry up to `now`
+    pub fn overlap(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Duration {
+        let start = from.map_or(self.start, |from| self.start.max(from));
+        let end = self.end.unwrap_or(now);
+        let end = to.map_or(end, |to| end.min(to));
+        (end - start).max(Duration::zero())
+    }
+
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        self.overlap(None, None, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, hour, min, 0).unwrap()
+    }
+
+    fn entry(start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> TimeEntry {
+        TimeEntry { start, end }
+    }
+
+    #[test]
+    fn counts_whole_entry_inside_range() {
+        let e = entry(at(9, 0), Some(at(10, 30)));
+        assert_eq!(
+            e.overlap(Some(at(8, 0)), Some(at(12, 0)), at(18, 0)),
+            Duration::minutes(90)
+        );
+        assert_eq!(e.overlap(None, None, at(18, 0)), Duration::minutes(90));
+    }
+
+    #[test]
+    fn clips_entry_to_range() {
+        let e = entry(at(9, 0), Some(at(11, 0)));
+        let now = at(18, 0);
+        assert_eq!(e.overlap(Some(at(10, 0)), None, now), Duration::hours(1));
+        assert_eq!(e.overlap(None, Some(at(9, 15)), now), Duration::minutes(15));
+        assert_eq!(
+            e.overlap(Some(at(9, 30)), Some(at(10, 0)), now),
+            Duration::minutes(30)
+        );
+    }
+
+    #[test]
+    fn entry_outside_range_counts_nothing() {
+        let e = entry(at(9, 0), Some(at(10, 0)));
+        let now = at(18, 0);
+        assert_eq!(e.overlap(Some(at(11, 0)), None, now), Duration::zero());
+        assert_eq!(e.overlap(None, Some(at(8, 0)), now), Duration::zero());
+    }
+
+    #[test]
+    fn running_entry_counts_up_to_now() {
+        let e = entry(at(9, 0), None);
+        assert!(e.is_running());
+        assert_eq!(e.duration(at(9, 45)), Duration::minutes(45));
+        assert_eq!(
+            e.overlap(None, Some(at(9, 30)), at(9, 45)),
+            Duration::minutes(30)
+        );
+        assert_eq!(
+            e.overlap(Some(at(10, 0)), None, at(9, 45)),
+            Duration::zero()
+        );
+    }
+}
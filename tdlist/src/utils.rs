@@ -1,61 +1,283 @@
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Local, NaiveDate, TimeZone, Timelike};
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone,
+    Utc,
+};
 
-use crate::app::{App, Id};
+use crate::app::Id;
 use crate::configuration::Settings;
+use crate::day_of_week::DayOfWeek;
+use crate::due_date::DueDate;
 use crate::task::Task;
 
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 pub fn load_tasks(file: PathBuf) -> HashMap<Id, Task> {
     let file = fs::read_to_string(file).expect("Unable to read file");
-    let tasks_map: HashMap<Id, Task> =
+    let mut tasks_map: HashMap<Id, Task> =
         serde_json::from_str(&file).expect("Unable to parse database file");
+    tasks_map.values_mut().for_each(Task::migrate);
     tasks_map
 }
 
-pub fn save_tasks(file: PathBuf, app: &App) {
-    let file = fs::File::create(file).expect("Unable to create file");
-    serde_json::to_writer(file, &app.tasks).expect("Unable to write file")
+/// Writes to a temporary file first so a failed write never leaves a
+/// half-written database behind
+pub fn save_tasks(file: PathBuf, tasks: &HashMap<Id, Task>) {
+    let tmp = file.with_extension("json.tmp");
+    let writer = fs::File::create(&tmp).expect("Unable to create file");
+    serde_json::to_writer(writer, tasks).expect("Unable to write file");
+    fs::rename(&tmp, &file).expect("Unable to replace database file")
 }
 
-pub fn get_today() -> DateTime<Local> {
-    let today = Local::now().date_naive().and_hms_opt(23, 59, 59).unwrap();
-    Local.from_local_datetime(&today).unwrap()
+/// Turns a wall clock time in `tz` into an instant without panicking on DST
+/// transitions. Ambiguous times (clocks going back) resolve to the earlier
+/// instant; times inside a gap (clocks going forward) are shifted past it.
+pub fn resolve_local<Tz: TimeZone>(tz: &Tz, naive: &NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(naive) {
+        LocalResult::Single(dt) => dt.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        LocalResult::None => {
+            // Interpret the time with the offset in effect before the gap
+            let before = *naive - Duration::days(1);
+            let offset = match tz.offset_from_local_datetime(&before) {
+                LocalResult::Single(o) | LocalResult::Ambiguous(o, _) => o.fix(),
+                LocalResult::None => Utc.fix(),
+            };
+            (*naive - offset).and_utc()
+        }
+    }
+}
+
+pub fn to_local(dt: &DateTime<Utc>) -> DateTime<Local> {
+    dt.with_timezone(&Local)
+}
+
+pub fn get_today() -> DueDate {
+    DueDate::Date(Local::now().date_naive())
+}
+
+pub fn parse_date(s: &str, settings: &Settings) -> Result<DueDate> {
+    parse_date_in(s, &Local, settings)
+}
+
+/// Parses `today`, `tomorrow`, a weekday like `monday` (the next one after
+/// today) or `+3d`, falling back to the configured input formats
+pub fn parse_relative_date(s: &str, settings: &Settings) -> Result<DueDate> {
+    parse_relative_date_in(s, &Local, settings)
+}
+
+/// Like `parse_relative_date`, with today and any time taken as wall clock
+/// time in `tz`
+pub fn parse_relative_date_in<Tz: TimeZone>(
+    s: &str,
+    tz: &Tz,
+    settings: &Settings,
+) -> Result<DueDate> {
+    let today = Utc::now().with_timezone(tz).date_naive();
+    let lower = s.trim().to_lowercase();
+    let lower = lower.strip_prefix("next ").unwrap_or(&lower);
+    match lower {
+        "today" => return Ok(DueDate::Date(today)),
+        "tomorrow" => return Ok(DueDate::Date(today + Duration::days(1))),
+        _ => {}
+    }
+    if let Ok(day) = DayOfWeek::from_str(lower) {
+        let ahead = (7 + day.to_int() - today.weekday().number_from_monday() - 1) % 7 + 1;
+        return Ok(DueDate::Date(today + Duration::days(ahead.into())));
+    }
+    if let Some(offset) = lower.strip_prefix('+') {
+        let days = parse_duration(offset)?.num_days();
+        return Ok(DueDate::Date(today + Duration::days(days)));
+    }
+    parse_date_in(s, tz, settings)
 }
 
-pub fn parse_date(s: &str, settings: &Settings) -> Result<DateTime<Local>> {
+/// Parses a date typed by the user as a wall clock time in `tz`
+pub fn parse_date_in<Tz: TimeZone>(s: &str, tz: &Tz, settings: &Settings) -> Result<DueDate> {
     let datetime_format = settings.date_formats.input_datetime_format.as_str();
     let date_format = settings.date_formats.input_date_format.as_str();
 
-    let attempt_datetime = Local.datetime_from_str(s, datetime_format);
+    let attempt_datetime = NaiveDateTime::parse_from_str(s, datetime_format);
     let attempt_date = NaiveDate::parse_from_str(s, date_format);
 
     if let Ok(datetime) = attempt_datetime {
-        Ok(datetime)
+        Ok(DueDate::DateTime(resolve_local(tz, &datetime)))
     } else if let Ok(date) = attempt_date {
-        let datetime = date.and_hms_opt(23, 59, 59).unwrap();
-        Ok(Local.from_local_datetime(&datetime).unwrap())
+        Ok(DueDate::Date(date))
     } else {
         Err(anyhow!("Unable to parse date"))
     }
 }
 
-fn date_has_time(date: &DateTime<Local>) -> bool {
-    let time = date.time();
-    if time.hour() == 23 && time.minute() == 59 {
-        return false;
+/// Formats a due date in the viewer's local timezone
+pub fn date_to_display_str(date: &DueDate, settings: &Settings) -> String {
+    match date {
+        DueDate::Date(date) => date
+            .format(&settings.date_formats.display_date_format)
+            .to_string(),
+        DueDate::DateTime(dt) => to_local(dt)
+            .format(&settings.date_formats.display_datetime_format)
+            .to_string(),
     }
-    true
 }
 
-pub fn date_to_display_str(dt: &DateTime<Local>, settings: &Settings) -> String {
-    let format = if date_has_time(dt) {
-        settings.date_formats.display_datetime_format.clone()
-    } else {
-        settings.date_formats.display_date_format.clone()
-    };
-    dt.format(format.as_str()).to_string()
+/// The instant `age` before now
+pub fn time_ago(age: Duration) -> Result<DateTime<Utc>> {
+    Utc::now()
+        .checked_sub_signed(age)
+        .ok_or_else(|| anyhow!("{} ago is out of range", duration_to_display_str(&age)))
+}
+
+/// Parses durations like `30m`, `2h`, `1d`, `1w` or combinations like `1h30m`
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(anyhow!("Duration cannot be empty"));
+    }
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: i64 = number
+            .parse()
+            .map_err(|_| anyhow!("Invalid duration: {}", s))?;
+        let part = match c.to_ascii_lowercase() {
+            's' => Duration::try_seconds(n),
+            'm' => Duration::try_minutes(n),
+            'h' => Duration::try_hours(n),
+            'd' => Duration::try_days(n),
+            'w' => Duration::try_weeks(n),
+            _ => return Err(anyhow!("Invalid duration unit '{}' in {}", c, s)),
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(|| anyhow!("Duration {} is too long", s))?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(anyhow!("Duration {} is missing a unit", s));
+    }
+    Ok(total)
+}
+
+/// A duration with an optional sign, e.g. +1w or -2d
+pub fn parse_signed_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    match s.strip_prefix('-') {
+        Some(rest) => Ok(-parse_duration(rest)?),
+        None => parse_duration(s.strip_prefix('+').unwrap_or(s)),
+    }
+}
+
+/// Formats a duration in the same form `parse_duration` reads
+pub fn duration_to_display_str(d: &Duration) -> String {
+    let mut seconds = d.num_seconds();
+    if seconds == 0 {
+        return "0m".to_string();
+    }
+    let sign = if seconds < 0 { "-" } else { "" };
+    seconds = seconds.abs();
+
+    let units = [
+        ("w", 604800),
+        ("d", 86400),
+        ("h", 3600),
+        ("m", 60),
+        ("s", 1),
+    ];
+    let mut s = sign.to_string();
+    for (unit, size) in units {
+        if seconds >= size {
+            s.push_str(&format!("{}{}", seconds / size, unit));
+            seconds %= size;
+        }
+    }
+    s
+}
+
+/// Finds http(s) urls in free text, including inside Markdown links
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut rest = text;
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || "()<>[]\"'`".contains(c))
+            .unwrap_or(candidate.len());
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        if !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+        rest = &candidate[end..];
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+    use chrono_tz::Europe::Berlin;
+
+    fn berlin(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        let naive = NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap();
+        resolve_local(&Berlin, &naive)
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn resolves_ordinary_times() {
+        assert_eq!(berlin(2025, 1, 15, 9, 0), utc(2025, 1, 15, 8, 0));
+        assert_eq!(berlin(2025, 6, 15, 9, 0), utc(2025, 6, 15, 7, 0));
+    }
+
+    #[test]
+    fn shifts_times_in_a_gap_past_it() {
+        // Clocks went from 02:00 to 03:00, so 02:30 never happened
+        assert_eq!(berlin(2025, 3, 30, 2, 30), utc(2025, 3, 30, 1, 30));
+        assert_eq!(berlin(2025, 3, 30, 2, 30).with_timezone(&Berlin).hour(), 3);
+    }
+
+    #[test]
+    fn picks_the_earlier_of_repeated_times() {
+        // Clocks went from 03:00 back to 02:00, so 02:30 happened twice
+        assert_eq!(berlin(2025, 10, 26, 2, 30), utc(2025, 10, 26, 0, 30));
+        assert_eq!(berlin(2025, 10, 26, 3, 30), utc(2025, 10, 26, 2, 30));
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_duration(" 1h30m ").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("1W2D").unwrap(), Duration::days(9));
+        assert_eq!(parse_duration("45s").unwrap(), Duration::seconds(45));
+    }
+
+    #[test]
+    fn rejects_bad_durations() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("2y").is_err());
+        assert!(parse_duration("-1d").is_err());
+        assert!(parse_duration("99999999999999d").is_err());
+        assert!(parse_duration("99999999999999999999d").is_err());
+    }
 }